@@ -26,6 +26,8 @@ pub enum VaultError {
     Unauthorized,
     #[msg("Invalid mint authority: vault must be mint authority")]
     InvalidMintAuthority,
+    #[msg("Mint does not match the vault's")]
+    InvalidMint,
     #[msg("LTV too high: maximum allowed is 80%")]
     LTVTooHigh,
     #[msg("Invalid threshold: liquidation threshold must be greater than base LTV")]
@@ -36,4 +38,84 @@ pub enum VaultError {
     CooldownAlreadyStarted,
     #[msg("This instruction is only available on devnet")]
     DevnetOnly,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Loan has no outstanding debt")]
+    NoOutstandingDebt,
+    #[msg("Borrow exceeds the maximum LTV for this loan")]
+    BorrowExceedsLtv,
+    #[msg("Withdrawal amount exceeds deposited collateral")]
+    InsufficientCollateral,
+    #[msg("Withdrawal would push the loan above its borrow LTV")]
+    WithdrawalExceedsLtv,
+    #[msg("Withdrawal amount exceeds the amount requested at cooldown start")]
+    WithdrawalExceedsRequest,
+    #[msg("Close factor must be between 1 and 10000 bps")]
+    InvalidCloseFactor,
+    #[msg("Loan is not underwater, use liquidate_loan")]
+    LoanNotUnderwater,
+    #[msg("No bad debt outstanding")]
+    NoBadDebt,
+    #[msg("Insurance reserve must be used before bad debt is socialized")]
+    InsuranceNotExhausted,
+    #[msg("Stability pool has no jUSDi to absorb liquidations")]
+    StabilityPoolEmpty,
+    #[msg("Snapshot of the deposit's stability pool epoch was not passed in")]
+    StabilityEpochMissing,
+    #[msg("Instruction not available in the vault's current liquidation mode")]
+    WrongLiquidationMode,
+    #[msg("Invalid auction parameters")]
+    InvalidAuctionParams,
+    #[msg("Loan is still unhealthy")]
+    LoanUnhealthy,
+    #[msg("Stability fee exceeds maximum")]
+    StabilityFeeTooHigh,
+    #[msg("Change must be queued through the timelock")]
+    TimelockActive,
+    #[msg("Timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Invalid timelock delay")]
+    InvalidTimelockDelay,
+    #[msg("Invalid role key")]
+    InvalidRole,
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+    #[msg("Vault is in global settlement")]
+    VaultSettled,
+    #[msg("Vault is not in global settlement")]
+    VaultNotSettled,
+    #[msg("Nothing to redeem")]
+    NothingToRedeem,
+    #[msg("Oracle confidence interval too wide")]
+    OracleConfidenceTooWide,
+    #[msg("Invalid oracle parameters")]
+    InvalidOracleParams,
+    #[msg("Not enough fresh oracle sources")]
+    NotEnoughOracleSources,
+    #[msg("A configured oracle source account was not passed in")]
+    OracleSourceMissing,
+    #[msg("Not enough recent price history for the TWAP")]
+    TwapUnavailable,
+    #[msg("Price was recorded too recently")]
+    ObservationTooSoon,
+    #[msg("Invalid TWAP window")]
+    InvalidTwapWindow,
+    #[msg("Borrowing halted by the price circuit breaker")]
+    BorrowHalted,
+    #[msg("Invalid circuit breaker parameters")]
+    InvalidBreakerParams,
+    #[msg("This instruction is only available in mock-oracle builds")]
+    MockOracleOnly,
+    #[msg("Invalid stake pool account")]
+    InvalidStakePool,
+    #[msg("Stake pool has not been updated this epoch")]
+    StakePoolNotUpdated,
+    #[msg("Invalid yield source")]
+    InvalidYieldSource,
+    #[msg("Reward rate exceeds maximum")]
+    RewardRateTooHigh,
+    #[msg("Token account is not owned by the treasury")]
+    InvalidTreasuryAccount,
+    #[msg("No fees to collect")]
+    NoFeesToCollect,
 }
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::state::{RiskParams, ParamChange, Role, OracleSource};
 
 #[event]
 pub struct LoanCreated {
@@ -9,12 +10,36 @@ pub struct LoanCreated {
     pub guardian_pubkey: Pubkey,
 }
 
+#[event]
+pub struct CollateralAdded {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub total_collateral: u64,
+}
+
+#[event]
+pub struct LoanBorrowed {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub total_debt: u64,
+    pub ltv_bps: u16,
+}
+
 #[event]
 pub struct RewardHarvested {
     pub user: Pubkey,
     pub rewards_earned: u64,
     pub fee_taken: u64,
     pub debt_reduced: u64,
+    /// Simulated APY the rewards were earned at; 0 for stake-pool yield
+    pub reward_rate_bps_per_year: u16,
+}
+
+#[event]
+pub struct LoanRepaid {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub remaining_debt: u64,
 }
 
 #[event]
@@ -29,11 +54,256 @@ pub struct LoanLiquidated {
     pub liquidator: Pubkey,
     pub collateral_seized: u64,
     pub debt_repaid: u64,
+    pub penalty_amount: u64,
+    pub collateral_refunded: u64,
+    pub remaining_debt: u64,
+}
+
+#[event]
+pub struct BadDebtRecorded {
+    pub user: Pubkey,
+    pub liquidator: Pubkey,
+    pub collateral_seized: u64,
+    pub debt_repaid: u64,
+    pub shortfall: u64,
+    pub total_bad_debt: u64,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub amount: u64,
+    pub treasury_account: Pubkey,
+    pub total_debt: u64,
+}
+
+#[event]
+pub struct InsuranceFunded {
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub reserve_balance: u64,
+}
+
+#[event]
+pub struct BadDebtCovered {
+    pub amount: u64,
+    pub remaining_bad_debt: u64,
+}
+
+#[event]
+pub struct BadDebtSocialized {
+    pub amount: u64,
+    pub remaining_bad_debt: u64,
+}
+
+#[event]
+pub struct StabilityDeposited {
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub shares: u128,
+    pub collateral_claimed: u64,
+}
+
+#[event]
+pub struct StabilityWithdrawn {
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub shares: u128,
+    pub collateral_claimed: u64,
+}
+
+#[event]
+pub struct StabilityCollateralClaimed {
+    pub depositor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct StabilityEpochEnded {
+    pub epoch: u64,
+    pub collateral_per_share: u128,
+}
+
+#[event]
+pub struct AuctionStarted {
+    pub user: Pubkey,
+    pub keeper: Pubkey,
+    pub debt_amount: u64,
+    pub collateral_amount: u64,
+}
+
+#[event]
+pub struct AuctionBid {
+    pub user: Pubkey,
+    pub bidder: Pubkey,
+    pub discount_bps: u16,
+    pub debt_repaid: u64,
+    pub collateral_sold: u64,
+    pub remaining_debt: u64,
+}
+
+#[event]
+pub struct AuctionClosed {
+    pub user: Pubkey,
+    pub remaining_debt: u64,
+}
+
+#[event]
+pub struct CloseFactorUpdated {
+    pub old_close_factor_bps: u16,
+    pub new_close_factor_bps: u16,
+}
+
+#[event]
+pub struct LiquidationModeUpdated {
+    pub mode: u8,
+    pub auction_max_discount_bps: u16,
+    pub auction_duration_secs: i64,
+}
+
+#[event]
+pub struct RiskParamsUpdated {
+    pub old: RiskParams,
+    pub new: RiskParams,
+}
+
+#[event]
+pub struct StabilityFeeUpdated {
+    pub stability_fee_per_sec: u64,
+    pub borrow_index: u128,
+}
+
+#[event]
+pub struct OracleUpdated {
+    pub old_price_feed: Pubkey,
+    pub new_price_feed: Pubkey,
+}
+
+#[event]
+pub struct OracleParamsUpdated {
+    pub max_oracle_staleness_secs: u64,
+    pub max_confidence_bps: u16,
+    pub min_oracle_sources: u8,
+}
+
+#[event]
+pub struct CircuitBreakerTripped {
+    pub last_price: i64,
+    pub price: i64,
+    pub deviation_bps: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerReset {
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerUpdated {
+    pub max_price_deviation_bps: u16,
+    pub price_deviation_window_secs: i64,
+}
+
+#[event]
+pub struct YieldSourceUpdated {
+    pub yield_source: u8,
+    pub stake_pool: Pubkey,
+}
+
+#[event]
+pub struct RewardRateUpdated {
+    pub reward_rate_bps_per_year: u16,
+    pub max_harvest_reward: u64,
+}
+
+#[event]
+pub struct TwapWindowUpdated {
+    pub twap_window_secs: i64,
+}
+
+#[event]
+pub struct OracleSourceUpdated {
+    pub index: u8,
+    pub old: OracleSource,
+    pub new: OracleSource,
+}
+
+#[event]
+pub struct ManualPriceUpdated {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+#[event]
+pub struct ChangeQueued {
+    pub change: ParamChange,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ChangeExecuted {
+    pub change: ParamChange,
+}
+
+#[event]
+pub struct ChangeCancelled {
+    pub change: ParamChange,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityAccepted {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct RoleSet {
+    pub role: Role,
+    pub key: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub role: Role,
+    pub previous: Pubkey,
+}
+
+#[event]
+pub struct SettlementTriggered {
+    pub price: i64,
+    pub expo: i32,
+    pub total_collateral: u64,
+    pub total_debt: u64,
+}
+
+#[event]
+pub struct LoanSettled {
+    pub user: Pubkey,
+    pub debt_amount: u64,
+    pub collateral_retained: u64,
+    pub collateral_returned: u64,
+}
+
+#[event]
+pub struct JusdiRedeemed {
+    pub redeemer: Pubkey,
+    pub jusdi_burned: u64,
+    pub skr_received: u64,
 }
 
 #[event]
 pub struct EmergencyPause {
     pub timestamp: i64,
+    pub flags: u8,
+    pub pause_flags: u8,
 }
 
 #[event]
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use crate::state::{VaultState, UserLoan, PAUSE_DEPOSIT, YIELD_SOURCE_SIMULATED, YIELD_SOURCE_STAKE_POOL};
+use crate::events::CollateralAdded;
+use crate::errors::VaultError;
+use crate::stake_pool;
+
+#[derive(Accounts)]
+pub struct AddCollateral<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_loan", user.key().as_ref()],
+        bump = user_loan.bump,
+        constraint = user_loan.owner == user.key() @ VaultError::Unauthorized
+    )]
+    pub user_loan: Box<Account<'info, UserLoan>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = vault_state
+    )]
+    pub vault_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = user
+    )]
+    pub user_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
+    pub mock_skr_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Stake pool issuing the collateral - validated by constraint, parsed in handler
+    #[account(constraint = vault_state.yield_source == YIELD_SOURCE_SIMULATED || stake_pool.key() == vault_state.stake_pool @ VaultError::InvalidStakePool)]
+    pub stake_pool: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<AddCollateral>, skr_amount: u64) -> Result<()> {
+    require!(!ctx.accounts.vault_state.is_paused(PAUSE_DEPOSIT), VaultError::Paused);
+    require!(skr_amount > 0, VaultError::InvalidAmount);
+
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault_state.accrue_interest(current_timestamp)?;
+    let vault_state = &ctx.accounts.vault_state;
+    let ltv_bps = vault_state.ltv_for_balance(ctx.accounts.user_skr_account.amount)?;
+
+    // Transfer SKR
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.user_skr_account.to_account_info(),
+        to: ctx.accounts.vault_skr_account.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token::transfer(cpi_ctx, skr_amount)?;
+
+    // Update Loan State
+    let user_loan = &mut ctx.accounts.user_loan;
+    let collateral_before = user_loan.collateral_amount;
+    user_loan.collateral_amount = user_loan.collateral_amount.checked_add(skr_amount).ok_or(VaultError::MathOverflow)?;
+    user_loan.last_harvest = current_timestamp;
+    // New tokens join at today's exchange rate; blending the baselines keeps the gain the
+    // existing collateral hasn't harvested yet
+    if ctx.accounts.vault_state.yield_source == YIELD_SOURCE_STAKE_POOL {
+        let rate = stake_pool::current_exchange_rate(&ctx.accounts.stake_pool, &ctx.accounts.vault_state.skr_mint, Clock::get()?.epoch)?;
+        user_loan.last_exchange_rate = stake_pool::blended_rate(collateral_before, user_loan.last_exchange_rate, skr_amount, rate)?;
+    }
+
+    // Set LTV snapshot if this is fresh
+    if user_loan.collateral_amount == skr_amount {
+        user_loan.initial_ltv_bps = ltv_bps;
+    }
+
+    // Update Global Stats
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.total_collateral = vault_state.total_collateral.checked_add(skr_amount).ok_or(VaultError::MathOverflow)?;
+
+    emit!(CollateralAdded {
+        user: user_loan.owner,
+        amount: skr_amount,
+        total_collateral: user_loan.collateral_amount,
+    });
+
+    Ok(())
+}
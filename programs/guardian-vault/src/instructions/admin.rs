@@ -1,7 +1,10 @@
 use anchor_lang::prelude::*;
-use crate::state::{VaultState, GuardianList, GuardianInfo};
-use crate::events::{EmergencyPause, GuardianAdded};
+use crate::state::{VaultState, GuardianList, GuardianInfo, RiskParams, ParamChange, Role, OracleSource, ManualPriceFeed, LIQUIDATION_MODE_FIXED, LIQUIDATION_MODE_AUCTION, MAX_STABILITY_FEE_PER_SEC, PAUSE_ALL, MAX_ORACLE_SOURCES, ORACLE_KIND_NONE, ORACLE_KIND_PYTH_PUSH, MIN_TWAP_WINDOW_SECS, MAX_TWAP_WINDOW_SECS, YIELD_SOURCE_SIMULATED, YIELD_SOURCE_STAKE_POOL, MAX_REWARD_RATE_BPS_PER_YEAR, MAX_TIMELOCK_DELAY};
+use crate::events::{EmergencyPause, GuardianAdded, CloseFactorUpdated, LiquidationModeUpdated, StabilityFeeUpdated, RiskParamsUpdated, OracleUpdated, AuthorityProposed, AuthorityAccepted, RoleSet, RoleRevoked, OracleParamsUpdated, OracleSourceUpdated, ManualPriceUpdated, TwapWindowUpdated, CircuitBreakerReset, CircuitBreakerUpdated, YieldSourceUpdated, RewardRateUpdated};
 use crate::errors::VaultError;
+use crate::oracle::{self, MIN_PRICE_EXPO, MAX_PRICE_EXPO};
+use crate::stake_pool;
+use super::settlement;
 
 #[derive(Accounts)]
 pub struct AdminConfig<'info> {
@@ -17,6 +20,120 @@ pub struct AdminConfig<'info> {
     pub vault_state: Account<'info, VaultState>,
 }
 
+/// Signed by the pause guardian (or the authority)
+#[derive(Accounts)]
+pub struct PauseConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        constraint = vault_state.has_role(Role::PauseGuardian, &admin.key()) @ VaultError::Unauthorized
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+/// Signed by the risk admin (or the authority)
+#[derive(Accounts)]
+pub struct RiskConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        constraint = vault_state.has_role(Role::RiskAdmin, &admin.key()) @ VaultError::Unauthorized
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+/// Signed by the oracle admin (or the authority)
+#[derive(Accounts)]
+pub struct OracleConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        constraint = vault_state.has_role(Role::OracleAdmin, &admin.key()) @ VaultError::Unauthorized
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+/// Signed by the oracle admin (or the authority), with the feed being installed
+#[derive(Accounts)]
+pub struct SetOracleFeed<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        constraint = vault_state.has_role(Role::OracleAdmin, &admin.key()) @ VaultError::Unauthorized
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: Candidate price account - validated in the instruction
+    pub new_price_feed: UncheckedAccount<'info>,
+}
+
+/// Signed by the risk admin (or the authority), with the stake pool backing the collateral
+#[derive(Accounts)]
+pub struct SetYieldSource<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        constraint = vault_state.has_role(Role::RiskAdmin, &admin.key()) @ VaultError::Unauthorized
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: Stake pool account - validated in the instruction (ignored for simulated yield)
+    pub stake_pool: UncheckedAccount<'info>,
+}
+
+/// Signed by the oracle admin (or the authority)
+#[derive(Accounts)]
+pub struct SetManualPrice<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        constraint = vault_state.has_role(Role::OracleAdmin, &admin.key()) @ VaultError::Unauthorized
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + std::mem::size_of::<ManualPriceFeed>(),
+        seeds = [b"manual_price"],
+        bump
+    )]
+    pub manual_price_feed: Account<'info, ManualPriceFeed>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub pending_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        constraint = vault_state.pending_authority == pending_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
 #[derive(Accounts)]
 pub struct AddGuardian<'info> {
     #[account(mut)]
@@ -42,24 +159,422 @@ pub struct AddGuardian<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn emergency_pause(ctx: Context<AdminConfig>) -> Result<()> {
-    ctx.accounts.vault_state.paused = true;
+/// Pauses the operations in `flags` (see `PAUSE_*`), leaving the rest running.
+pub fn emergency_pause(ctx: Context<PauseConfig>, flags: u8) -> Result<()> {
+    require!(flags != 0 && flags & !PAUSE_ALL == 0, VaultError::InvalidPauseFlags);
+
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.pause_flags |= flags;
     emit!(EmergencyPause {
         timestamp: Clock::get()?.unix_timestamp,
+        flags,
+        pause_flags: vault_state.pause_flags,
     });
     Ok(())
 }
 
 // HIGH-05 FIX: Allow admin to unpause the vault
-pub fn unpause(ctx: Context<AdminConfig>) -> Result<()> {
-    ctx.accounts.vault_state.paused = false;
-    msg!("Vault unpaused at {}", Clock::get()?.unix_timestamp);
+pub fn unpause(ctx: Context<AdminConfig>, flags: u8) -> Result<()> {
+    require!(flags != 0 && flags & !PAUSE_ALL == 0, VaultError::InvalidPauseFlags);
+
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.pause_flags &= !flags;
+    msg!("Vault unpaused (flags {:#04x}) at {}; still paused: {:#04x}", flags, Clock::get()?.unix_timestamp, vault_state.pause_flags);
     Ok(())
 }
 
-pub fn update_oracle(ctx: Context<AdminConfig>, new_price_feed: Pubkey) -> Result<()> {
-    // Basic validation could check if account exists, but for now just update key
-    ctx.accounts.vault_state.skr_price_feed = new_price_feed;
+/// Step 1 of an authority transfer. Proposing `Pubkey::default()` cancels a pending transfer.
+pub fn propose_authority(ctx: Context<AdminConfig>, new_authority: Pubkey) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.pending_authority = new_authority;
+
+    emit!(AuthorityProposed {
+        authority: vault_state.authority,
+        pending_authority: new_authority,
+    });
+    Ok(())
+}
+
+/// Step 2: the proposed key signs to take over.
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+    let old_authority = vault_state.authority;
+    vault_state.authority = ctx.accounts.pending_authority.key();
+    vault_state.pending_authority = Pubkey::default();
+
+    emit!(AuthorityAccepted {
+        old_authority,
+        new_authority: vault_state.authority,
+    });
+    Ok(())
+}
+
+pub fn set_role(ctx: Context<AdminConfig>, role: Role, key: Pubkey) -> Result<()> {
+    require!(key != Pubkey::default(), VaultError::InvalidRole);
+    ctx.accounts.vault_state.roles.set(role, key);
+
+    emit!(RoleSet {
+        role,
+        key,
+    });
+    Ok(())
+}
+
+pub fn revoke_role(ctx: Context<AdminConfig>, role: Role) -> Result<()> {
+    let roles = &mut ctx.accounts.vault_state.roles;
+    let previous = roles.get(role);
+    roles.set(role, Pubkey::default());
+
+    emit!(RoleRevoked {
+        role,
+        previous,
+    });
+    Ok(())
+}
+
+pub fn update_oracle(ctx: Context<SetOracleFeed>) -> Result<()> {
+    let new_price_feed = ctx.accounts.new_price_feed.key();
+    let feed = [ctx.accounts.new_price_feed.to_account_info()];
+    apply_now(&mut ctx.accounts.vault_state, ParamChange::Oracle(new_price_feed), &feed)
+}
+
+pub(crate) fn pyth_push_source(feed: Pubkey) -> OracleSource {
+    OracleSource {
+        kind: ORACLE_KIND_PYTH_PUSH,
+        feed,
+        feed_id: [0; 32],
+    }
+}
+
+/// Rejects a feed that couldn't price SKR right now: wrong owner, unparseable, stale,
+/// or an out-of-range exponent. Disabling a slot needs no feed.
+pub(crate) fn validate_feed(vault_state: &VaultState, source: &OracleSource, feed: &AccountInfo) -> Result<()> {
+    if source.kind == ORACLE_KIND_NONE {
+        return Ok(());
+    }
+    oracle::validate_source(source, feed, vault_state, Clock::get()?.unix_timestamp)
+}
+
+/// Finds `source`'s feed among `accounts` and validates it.
+pub(crate) fn validate_feed_in(vault_state: &VaultState, source: &OracleSource, accounts: &[AccountInfo]) -> Result<()> {
+    if source.kind == ORACLE_KIND_NONE {
+        return Ok(());
+    }
+    let feed = accounts.iter().find(|account| *account.key == source.feed).ok_or(VaultError::InvalidPriceFeed)?;
+    validate_feed(vault_state, source, feed)
+}
+
+/// Points the first oracle source at a Pyth push feed.
+fn apply_oracle(vault_state: &mut VaultState, new_price_feed: Pubkey) -> Result<()> {
+    let old_price_feed = vault_state.oracle_sources[0].feed;
+    set_oracle_slot(vault_state, 0, pyth_push_source(new_price_feed))?;
+
+    emit!(OracleUpdated {
+        old_price_feed,
+        new_price_feed,
+    });
+    Ok(())
+}
+
+pub fn set_oracle_source(ctx: Context<SetOracleFeed>, index: u8, source: OracleSource) -> Result<()> {
+    let feed = [ctx.accounts.new_price_feed.to_account_info()];
+    apply_now(&mut ctx.accounts.vault_state, ParamChange::OracleSource(index, source), &feed)
+}
+
+fn apply_oracle_source(vault_state: &mut VaultState, index: u8, source: OracleSource) -> Result<()> {
+    let old = set_oracle_slot(vault_state, index, source)?;
+
+    emit!(OracleSourceUpdated {
+        index,
+        old,
+        new: source,
+    });
+    Ok(())
+}
+
+/// Replaces the source in slot `index`, returning the old one. A feed may only fill one
+/// slot, so a single account can't stand in for several sources in the median.
+fn set_oracle_slot(vault_state: &mut VaultState, index: u8, source: OracleSource) -> Result<OracleSource> {
+    require!((index as usize) < MAX_ORACLE_SOURCES, VaultError::InvalidOracleParams);
+    source.validate()?;
+
+    let source = if source.kind == ORACLE_KIND_NONE { OracleSource::default() } else { source };
+    let duplicate = vault_state.oracle_sources.iter().enumerate()
+        .any(|(i, other)| i != index as usize && other.kind != ORACLE_KIND_NONE && other.feed == source.feed);
+    require!(source.kind == ORACLE_KIND_NONE || !duplicate, VaultError::InvalidOracleParams);
+
+    let old = vault_state.oracle_sources[index as usize];
+    vault_state.oracle_sources[index as usize] = source;
+    Ok(old)
+}
+
+pub fn set_oracle_params(ctx: Context<OracleConfig>, max_oracle_staleness_secs: u64, max_confidence_bps: u16, min_oracle_sources: u8) -> Result<()> {
+    let change = ParamChange::OracleParams(max_oracle_staleness_secs, max_confidence_bps, min_oracle_sources);
+    apply_now(&mut ctx.accounts.vault_state, change, &[])
+}
+
+/// Sets the window of the TWAP that liquidations check alongside the spot price.
+pub fn set_twap_window(ctx: Context<OracleConfig>, twap_window_secs: i64) -> Result<()> {
+    apply_now(&mut ctx.accounts.vault_state, ParamChange::TwapWindow(twap_window_secs), &[])
+}
+
+/// Configures the borrow circuit breaker. A deviation of 0 disables it.
+pub fn set_circuit_breaker(ctx: Context<OracleConfig>, max_price_deviation_bps: u16, price_deviation_window_secs: i64) -> Result<()> {
+    let change = ParamChange::CircuitBreaker(max_price_deviation_bps, price_deviation_window_secs);
+    apply_now(&mut ctx.accounts.vault_state, change, &[])
+}
+
+/// Re-enables borrowing after the circuit breaker tripped. The next borrow's price
+/// becomes the new reference.
+pub fn reset_circuit_breaker(ctx: Context<AdminConfig>) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.breaker_tripped = false;
+    vault_state.last_price = 0;
+    vault_state.last_price_at = 0;
+
+    emit!(CircuitBreakerReset {
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Posts a price to the program-owned manual feed. Only counts towards pricing once the
+/// feed's address is configured as an `ORACLE_KIND_MANUAL` source. Under the timelock a
+/// price is queued instead, and the feed must already exist when it lands.
+pub fn set_manual_price(ctx: Context<SetManualPrice>, price: i64, conf: u64, expo: i32) -> Result<()> {
+    require!(ctx.accounts.vault_state.timelock_delay == 0, VaultError::TimelockActive);
+    validate_change(&ParamChange::ManualPrice(price, conf, expo))?;
+
+    let manual_price_feed = &mut ctx.accounts.manual_price_feed;
+    manual_price_feed.bump = *ctx.bumps.get("manual_price_feed").unwrap();
+    write_manual_price(manual_price_feed, price, conf, expo)
+}
+
+fn write_manual_price(manual_price_feed: &mut ManualPriceFeed, price: i64, conf: u64, expo: i32) -> Result<()> {
+    let publish_time = Clock::get()?.unix_timestamp;
+    manual_price_feed.price = price;
+    manual_price_feed.conf = conf;
+    manual_price_feed.expo = expo;
+    manual_price_feed.publish_time = publish_time;
+
+    emit!(ManualPriceUpdated {
+        price,
+        conf,
+        expo,
+        publish_time,
+    });
+    Ok(())
+}
+
+pub fn update_risk_params(ctx: Context<RiskConfig>, params: RiskParams) -> Result<()> {
+    apply_now(&mut ctx.accounts.vault_state, ParamChange::RiskParams(params), &[])
+}
+
+fn apply_risk_params(vault_state: &mut VaultState, params: RiskParams) -> Result<()> {
+    params.validate()?;
+
+    let old = vault_state.risk_params();
+    vault_state.set_risk_params(&params);
+
+    emit!(RiskParamsUpdated {
+        old,
+        new: params,
+    });
+    Ok(())
+}
+
+pub fn update_close_factor(ctx: Context<RiskConfig>, close_factor_bps: u16) -> Result<()> {
+    apply_now(&mut ctx.accounts.vault_state, ParamChange::CloseFactor(close_factor_bps), &[])
+}
+
+pub fn set_liquidation_mode(ctx: Context<RiskConfig>, mode: u8, auction_max_discount_bps: u16, auction_duration_secs: i64) -> Result<()> {
+    let change = ParamChange::LiquidationMode(mode, auction_max_discount_bps, auction_duration_secs);
+    apply_now(&mut ctx.accounts.vault_state, change, &[])
+}
+
+/// Chooses where harvest rewards come from. Stake-pool yield requires the pool that
+/// issues the collateral mint.
+pub fn set_yield_source(ctx: Context<SetYieldSource>, yield_source: u8) -> Result<()> {
+    let stake_pool = if yield_source == YIELD_SOURCE_STAKE_POOL { ctx.accounts.stake_pool.key() } else { Pubkey::default() };
+    let pool = [ctx.accounts.stake_pool.to_account_info()];
+    apply_now(&mut ctx.accounts.vault_state, ParamChange::YieldSource(yield_source, stake_pool), &pool)
+}
+
+/// Sets the APY and per-harvest cap of simulated yield (`max_harvest_reward` 0 = no cap).
+pub fn set_reward_rate(ctx: Context<RiskConfig>, reward_rate_bps_per_year: u16, max_harvest_reward: u64) -> Result<()> {
+    let change = ParamChange::RewardRate(reward_rate_bps_per_year, max_harvest_reward);
+    apply_now(&mut ctx.accounts.vault_state, change, &[])
+}
+
+/// Sets the per-second stability fee (scaled by INDEX_SCALE). Interest up to now is
+/// accrued at the old rate first.
+pub fn set_stability_fee(ctx: Context<RiskConfig>, stability_fee_per_sec: u64) -> Result<()> {
+    apply_now(&mut ctx.accounts.vault_state, ParamChange::StabilityFee(stability_fee_per_sec), &[])
+}
+
+/// Applies `change` straight away. Once `timelock_delay` is set, changes must be queued.
+pub(crate) fn apply_now(vault_state: &mut VaultState, change: ParamChange, accounts: &[AccountInfo]) -> Result<()> {
+    require!(vault_state.timelock_delay == 0, VaultError::TimelockActive);
+    validate_change(&change)?;
+    apply_change(vault_state, change, accounts)
+}
+
+/// Bounds checks for a change, run before it is applied or queued.
+pub(crate) fn validate_change(change: &ParamChange) -> Result<()> {
+    match *change {
+        ParamChange::RiskParams(params) => params.validate()?,
+        ParamChange::Oracle(_) => {}
+        ParamChange::TimelockDelay(delay) => {
+            require!((0..=MAX_TIMELOCK_DELAY).contains(&delay), VaultError::InvalidTimelockDelay);
+        }
+        ParamChange::OracleSource(_, source) => source.validate()?,
+        ParamChange::OracleParams(max_oracle_staleness_secs, max_confidence_bps, min_oracle_sources) => {
+            require!(max_oracle_staleness_secs > 0 && max_oracle_staleness_secs <= 3600, VaultError::InvalidOracleParams); // Max 1 hour
+            require!(max_confidence_bps > 0 && max_confidence_bps <= 1000, VaultError::InvalidOracleParams); // Max 10% of price
+            require!(min_oracle_sources > 0 && min_oracle_sources as usize <= MAX_ORACLE_SOURCES, VaultError::InvalidOracleParams);
+        }
+        ParamChange::CloseFactor(close_factor_bps) => {
+            require!(close_factor_bps > 0 && close_factor_bps <= 10000, VaultError::InvalidCloseFactor);
+        }
+        ParamChange::LiquidationMode(mode, auction_max_discount_bps, auction_duration_secs) => {
+            require!(mode == LIQUIDATION_MODE_FIXED || mode == LIQUIDATION_MODE_AUCTION, VaultError::InvalidAuctionParams);
+            // Checked in either mode: auctions left open by a switch to fixed keep pricing from them
+            require!(auction_max_discount_bps > 0 && auction_max_discount_bps <= 5000, VaultError::InvalidAuctionParams); // Max 50% discount
+            require!(auction_duration_secs > 0, VaultError::InvalidAuctionParams);
+        }
+        ParamChange::StabilityFee(stability_fee_per_sec) => {
+            require!(stability_fee_per_sec <= MAX_STABILITY_FEE_PER_SEC, VaultError::StabilityFeeTooHigh);
+        }
+        ParamChange::YieldSource(yield_source, _) => {
+            require!(yield_source == YIELD_SOURCE_SIMULATED || yield_source == YIELD_SOURCE_STAKE_POOL, VaultError::InvalidYieldSource);
+        }
+        ParamChange::RewardRate(reward_rate_bps_per_year, _) => {
+            require!(reward_rate_bps_per_year <= MAX_REWARD_RATE_BPS_PER_YEAR, VaultError::RewardRateTooHigh); // Max 100% APY
+        }
+        ParamChange::CircuitBreaker(max_price_deviation_bps, price_deviation_window_secs) => {
+            require!(max_price_deviation_bps <= 10000, VaultError::InvalidBreakerParams);
+            require!(price_deviation_window_secs > 0 && price_deviation_window_secs <= 24 * 60 * 60, VaultError::InvalidBreakerParams); // Max 1 day
+        }
+        ParamChange::TwapWindow(twap_window_secs) => {
+            require!(
+                (MIN_TWAP_WINDOW_SECS..=MAX_TWAP_WINDOW_SECS).contains(&twap_window_secs),
+                VaultError::InvalidTwapWindow
+            );
+        }
+        ParamChange::ManualPrice(price, _, expo) => {
+            require!(price > 0, VaultError::InvalidPriceFeed);
+            require!((-12..=0).contains(&expo), VaultError::InvalidOracleParams);
+        }
+        ParamChange::Settlement(price, expo) => {
+            require!(price > 0, VaultError::InvalidPriceFeed);
+            require!((MIN_PRICE_EXPO..=MAX_PRICE_EXPO).contains(&expo), VaultError::InvalidPriceFeed);
+        }
+    }
+    Ok(())
+}
+
+/// Applies a validated change. Feeds, pools and the manual price account it touches are
+/// looked up in `accounts` and checked as they are now.
+pub(crate) fn apply_change(vault_state: &mut VaultState, change: ParamChange, accounts: &[AccountInfo]) -> Result<()> {
+    match change {
+        ParamChange::RiskParams(params) => apply_risk_params(vault_state, params)?,
+        ParamChange::Oracle(new_price_feed) => {
+            validate_feed_in(vault_state, &pyth_push_source(new_price_feed), accounts)?;
+            apply_oracle(vault_state, new_price_feed)?
+        }
+        ParamChange::TimelockDelay(delay) => vault_state.timelock_delay = delay,
+        ParamChange::OracleSource(index, source) => {
+            validate_feed_in(vault_state, &source, accounts)?;
+            apply_oracle_source(vault_state, index, source)?
+        }
+        ParamChange::OracleParams(max_oracle_staleness_secs, max_confidence_bps, min_oracle_sources) => {
+            vault_state.max_oracle_staleness_secs = max_oracle_staleness_secs;
+            vault_state.max_confidence_bps = max_confidence_bps;
+            vault_state.min_oracle_sources = min_oracle_sources;
+
+            emit!(OracleParamsUpdated {
+                max_oracle_staleness_secs,
+                max_confidence_bps,
+                min_oracle_sources,
+            });
+        }
+        ParamChange::CloseFactor(close_factor_bps) => {
+            let old_close_factor_bps = vault_state.close_factor_bps;
+            vault_state.close_factor_bps = close_factor_bps;
+
+            emit!(CloseFactorUpdated {
+                old_close_factor_bps,
+                new_close_factor_bps: close_factor_bps,
+            });
+        }
+        ParamChange::LiquidationMode(mode, auction_max_discount_bps, auction_duration_secs) => {
+            vault_state.liquidation_mode = mode;
+            vault_state.auction_max_discount_bps = auction_max_discount_bps;
+            vault_state.auction_duration_secs = auction_duration_secs;
+
+            emit!(LiquidationModeUpdated {
+                mode,
+                auction_max_discount_bps,
+                auction_duration_secs,
+            });
+        }
+        ParamChange::StabilityFee(stability_fee_per_sec) => {
+            require!(!vault_state.is_settled(), VaultError::VaultSettled);
+            vault_state.accrue_interest(Clock::get()?.unix_timestamp)?;
+            vault_state.stability_fee_per_sec = stability_fee_per_sec;
+
+            emit!(StabilityFeeUpdated {
+                stability_fee_per_sec,
+                borrow_index: vault_state.borrow_index,
+            });
+        }
+        ParamChange::YieldSource(yield_source, stake_pool) => {
+            if yield_source == YIELD_SOURCE_STAKE_POOL {
+                let pool = accounts.iter().find(|account| *account.key == stake_pool).ok_or(VaultError::InvalidStakePool)?;
+                stake_pool::validate_pool(pool, &vault_state.skr_mint)?;
+            }
+            vault_state.yield_source = yield_source;
+            vault_state.stake_pool = stake_pool;
+
+            emit!(YieldSourceUpdated {
+                yield_source,
+                stake_pool,
+            });
+        }
+        ParamChange::RewardRate(reward_rate_bps_per_year, max_harvest_reward) => {
+            vault_state.reward_rate_bps_per_year = reward_rate_bps_per_year;
+            vault_state.max_harvest_reward = max_harvest_reward;
+
+            emit!(RewardRateUpdated {
+                reward_rate_bps_per_year,
+                max_harvest_reward,
+            });
+        }
+        ParamChange::CircuitBreaker(max_price_deviation_bps, price_deviation_window_secs) => {
+            vault_state.max_price_deviation_bps = max_price_deviation_bps;
+            vault_state.price_deviation_window_secs = price_deviation_window_secs;
+
+            emit!(CircuitBreakerUpdated {
+                max_price_deviation_bps,
+                price_deviation_window_secs,
+            });
+        }
+        ParamChange::TwapWindow(twap_window_secs) => {
+            vault_state.twap_window_secs = twap_window_secs;
+
+            emit!(TwapWindowUpdated {
+                twap_window_secs,
+            });
+        }
+        ParamChange::ManualPrice(price, conf, expo) => {
+            let (address, _) = Pubkey::find_program_address(&[b"manual_price"], &crate::ID);
+            let info = accounts.iter().find(|account| *account.key == address).ok_or(VaultError::InvalidPriceFeed)?;
+            let mut manual_price_feed = Account::<ManualPriceFeed>::try_from(info)?;
+            write_manual_price(&mut manual_price_feed, price, conf, expo)?;
+            manual_price_feed.exit(&crate::ID)?;
+        }
+        ParamChange::Settlement(price, expo) => settlement::apply_settlement(vault_state, price, expo)?,
+    }
     Ok(())
 }
 
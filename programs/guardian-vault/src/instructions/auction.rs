@@ -0,0 +1,334 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, Burn};
+use crate::state::{VaultState, UserLoan, PriceHistory, LiquidationAuction, LIQUIDATION_MODE_AUCTION, PAUSE_LIQUIDATE};
+use crate::events::{AuctionStarted, AuctionBid, AuctionClosed};
+use crate::errors::VaultError;
+use crate::oracle;
+use super::liquidate_loan::{require_unhealthy, size_liquidation, twap_price, LiquidationAmounts};
+
+#[derive(Accounts)]
+pub struct StartAuction<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_loan", user_loan.owner.as_ref()],
+        bump = user_loan.bump
+    )]
+    pub user_loan: Box<Account<'info, UserLoan>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        init,
+        payer = keeper,
+        space = 8 + std::mem::size_of::<LiquidationAuction>(),
+        seeds = [b"auction", user_loan.key().as_ref()],
+        bump
+    )]
+    pub auction: Box<Account<'info, LiquidationAuction>>,
+
+    /// CHECK: Oracle price account - validated by constraint
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
+    pub skr_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"price_history"],
+        bump = price_history.bump
+    )]
+    pub price_history: Box<Account<'info, PriceHistory>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BidAuction<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_loan", user_loan.owner.as_ref()],
+        bump = user_loan.bump
+    )]
+    pub user_loan: Box<Account<'info, UserLoan>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", user_loan.key().as_ref()],
+        bump = auction.bump,
+        has_one = keeper
+    )]
+    pub auction: Box<Account<'info, LiquidationAuction>>,
+
+    /// CHECK: Auction starter; receives the auction's rent when it closes
+    #[account(mut)]
+    pub keeper: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = vault_state
+    )]
+    pub vault_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = bidder
+    )]
+    pub bidder_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = bidder
+    )]
+    pub bidder_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    // Surplus collateral is returned here once the debt is cleared
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = user_loan.owner
+    )]
+    pub user_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = vault_state.jusdi_mint @ VaultError::InvalidMint
+    )]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
+    pub mock_skr_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Oracle price account - validated by constraint
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
+    pub skr_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"price_history"],
+        bump = price_history.bump
+    )]
+    pub price_history: Box<Account<'info, PriceHistory>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAuction<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_loan", user_loan.owner.as_ref()],
+        bump = user_loan.bump
+    )]
+    pub user_loan: Box<Account<'info, UserLoan>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", user_loan.key().as_ref()],
+        bump = auction.bump,
+        has_one = keeper,
+        close = keeper
+    )]
+    pub auction: Box<Account<'info, LiquidationAuction>>,
+
+    /// CHECK: Auction starter; receives the auction's rent
+    #[account(mut)]
+    pub keeper: UncheckedAccount<'info>,
+
+    /// CHECK: Oracle price account - validated by constraint
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
+    pub skr_price_feed: UncheckedAccount<'info>,
+}
+
+pub fn start_auction(ctx: Context<StartAuction>) -> Result<()> {
+    require!(!ctx.accounts.vault_state.is_paused(PAUSE_LIQUIDATE), VaultError::Paused);
+    require!(ctx.accounts.vault_state.liquidation_mode == LIQUIDATION_MODE_AUCTION, VaultError::WrongLiquidationMode);
+
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault_state.accrue_interest(current_timestamp)?;
+    ctx.accounts.user_loan.sync_debt(ctx.accounts.vault_state.borrow_index)?;
+    let vault_state = &ctx.accounts.vault_state;
+    let price = oracle::get_skr_price(&ctx.accounts.skr_price_feed.to_account_info(), ctx.remaining_accounts, vault_state, current_timestamp)?;
+    require_unhealthy(vault_state, &ctx.accounts.user_loan, &price)?;
+    require_unhealthy(vault_state, &ctx.accounts.user_loan, &twap_price(vault_state, &ctx.accounts.price_history, current_timestamp)?)?;
+
+    let auction = &mut ctx.accounts.auction;
+    auction.user_loan = ctx.accounts.user_loan.key();
+    auction.keeper = ctx.accounts.keeper.key();
+    auction.started_at = current_timestamp;
+    auction.bump = *ctx.bumps.get("auction").unwrap();
+
+    emit!(AuctionStarted {
+        user: ctx.accounts.user_loan.owner,
+        keeper: auction.keeper,
+        debt_amount: ctx.accounts.user_loan.debt_amount,
+        collateral_amount: ctx.accounts.user_loan.collateral_amount,
+    });
+
+    Ok(())
+}
+
+/// Bids on an open auction. An auction started before a switch away from auction mode can
+/// still be bid on until it closes.
+pub fn bid(ctx: Context<BidAuction>, repay_amount: u64) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+    require!(!vault_state.is_paused(PAUSE_LIQUIDATE), VaultError::Paused);
+    require!(repay_amount > 0, VaultError::InvalidAmount);
+
+    // 1. Check Health
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    vault_state.accrue_interest(current_timestamp)?;
+    let borrow_index = vault_state.borrow_index;
+    ctx.accounts.user_loan.sync_debt(borrow_index)?;
+    let user_loan = &ctx.accounts.user_loan;
+    let price = oracle::get_skr_price(&ctx.accounts.skr_price_feed.to_account_info(), ctx.remaining_accounts, vault_state, current_timestamp)?;
+    require_unhealthy(vault_state, user_loan, &price)?;
+    require_unhealthy(vault_state, user_loan, &twap_price(vault_state, &ctx.accounts.price_history, current_timestamp)?)?;
+
+    // 2. Price the chunk: collateral sells at the oracle price less the current discount,
+    // i.e. the bidder receives debt / (1 - discount) worth of SKR.
+    let discount_bps = ctx.accounts.auction.discount_bps(vault_state, current_timestamp);
+    let seize_multiplier_bps = 10000u128
+        .checked_mul(10000).ok_or(VaultError::MathOverflow)?
+        .checked_div(10000u128.checked_sub(discount_bps as u128).ok_or(VaultError::MathOverflow)?)
+        .ok_or(VaultError::MathOverflow)?;
+
+    let LiquidationAmounts {
+        debt_repaid,
+        collateral_seized,
+        collateral_refunded,
+        remaining_debt,
+        remaining_collateral,
+        ..
+    } = size_liquidation(user_loan, repay_amount, seize_multiplier_bps, vault_state.skr_decimals, &price)?;
+
+    // 3. Burn jUSDi from Bidder
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.jusdi_mint.to_account_info(),
+            from: ctx.accounts.bidder_jusdi_account.to_account_info(),
+            authority: ctx.accounts.bidder.to_account_info(),
+        },
+    );
+    token::burn(cpi_ctx, debt_repaid)?;
+
+    let collateral_released = collateral_seized.checked_add(collateral_refunded).ok_or(VaultError::MathOverflow)?;
+    vault_state.reduce_total_debt(debt_repaid);
+    vault_state.total_collateral = vault_state.total_collateral.checked_sub(collateral_released).ok_or(VaultError::MathOverflow)?;
+
+    // 4. Deliver Collateral
+    let seeds = &[
+        b"vault_state".as_ref(),
+        &[vault_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.vault_skr_account.to_account_info(),
+            to: ctx.accounts.bidder_skr_account.to_account_info(),
+            authority: vault_state.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, collateral_seized)?;
+
+    // Refund Surplus to Borrower
+    if collateral_refunded > 0 {
+        let refund_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_skr_account.to_account_info(),
+                to: ctx.accounts.user_skr_account.to_account_info(),
+                authority: vault_state.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(refund_ctx, collateral_refunded)?;
+    }
+
+    // 5. Update Loan State
+    let user_loan = &mut ctx.accounts.user_loan;
+    user_loan.reduce_debt(debt_repaid, borrow_index)?;
+    user_loan.collateral_amount = remaining_collateral;
+
+    emit!(AuctionBid {
+        user: user_loan.owner,
+        bidder: ctx.accounts.bidder.key(),
+        discount_bps,
+        debt_repaid,
+        collateral_sold: collateral_seized,
+        remaining_debt,
+    });
+
+    // 6. Wind down: the auction ends with the debt (or collateral); the loan closes once both are gone
+    if remaining_debt == 0 || remaining_collateral == 0 {
+        ctx.accounts.auction.close(ctx.accounts.keeper.to_account_info())?;
+
+        emit!(AuctionClosed {
+            user: user_loan.owner,
+            remaining_debt,
+        });
+    }
+
+    if user_loan.debt_amount == 0 && user_loan.collateral_amount == 0 {
+        let source_account_info = user_loan.to_account_info();
+        let dest_account_info = ctx.accounts.bidder.to_account_info();
+
+        let dest_lamports = dest_account_info.lamports();
+        **dest_account_info.lamports.borrow_mut() = dest_lamports.checked_add(source_account_info.lamports()).ok_or(VaultError::MathOverflow)?;
+        **source_account_info.lamports.borrow_mut() = 0;
+    }
+
+    Ok(())
+}
+
+/// Ends an auction whose loan has become healthy again (price recovered or borrower repaid),
+/// or one left open after the vault switched away from auction mode.
+pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault_state.accrue_interest(current_timestamp)?;
+    ctx.accounts.user_loan.sync_debt(ctx.accounts.vault_state.borrow_index)?;
+
+    if ctx.accounts.vault_state.liquidation_mode == LIQUIDATION_MODE_AUCTION {
+        let price = oracle::get_skr_price(&ctx.accounts.skr_price_feed.to_account_info(), ctx.remaining_accounts, &ctx.accounts.vault_state, current_timestamp)?;
+        let still_unhealthy = require_unhealthy(&ctx.accounts.vault_state, &ctx.accounts.user_loan, &price).is_ok();
+        require!(!still_unhealthy, VaultError::LoanUnhealthy);
+    }
+
+    emit!(AuctionClosed {
+        user: ctx.accounts.user_loan.owner,
+        remaining_debt: ctx.accounts.user_loan.debt_amount,
+    });
+
+    // Account closed automatically via `close = keeper`
+
+    Ok(())
+}
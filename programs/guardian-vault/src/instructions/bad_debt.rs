@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, Burn};
+use crate::state::{VaultState, Role};
+use crate::events::{InsuranceFunded, BadDebtCovered, BadDebtSocialized};
+use crate::errors::VaultError;
+
+// The insurance reserve sits in the vault's jUSDi ATA and is tracked in
+// `insurance_balance`: anyone can top it up, and the treasury admin burns from it to
+// retire bad debt. Tokens sent to the ATA directly are not part of the reserve.
+
+#[derive(Accounts)]
+pub struct FundInsurance<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = funder
+    )]
+    pub funder_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = vault_state
+    )]
+    pub vault_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = vault_state.jusdi_mint @ VaultError::InvalidMint)]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ManageBadDebt<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        constraint = vault_state.has_role(Role::TreasuryAdmin, &admin.key()) @ VaultError::Unauthorized
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = vault_state
+    )]
+    pub vault_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = vault_state.jusdi_mint @ VaultError::InvalidMint
+    )]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn fund_insurance(ctx: Context<FundInsurance>, amount: u64) -> Result<()> {
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.funder_jusdi_account.to_account_info(),
+            to: ctx.accounts.vault_jusdi_account.to_account_info(),
+            authority: ctx.accounts.funder.to_account_info(),
+        },
+    );
+    token::transfer(cpi_ctx, amount)?;
+
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.insurance_balance = vault_state.insurance_balance.checked_add(amount).ok_or(VaultError::MathOverflow)?;
+
+    emit!(InsuranceFunded {
+        funder: ctx.accounts.funder.key(),
+        amount,
+        reserve_balance: vault_state.insurance_balance,
+    });
+
+    Ok(())
+}
+
+/// Burns jUSDi from the insurance reserve to retire up to `amount` of bad debt.
+pub fn cover_bad_debt(ctx: Context<ManageBadDebt>, amount: u64) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+    require!(vault_state.bad_debt > 0, VaultError::NoBadDebt);
+
+    let covered = amount
+        .min(vault_state.bad_debt)
+        .min(vault_state.insurance_balance);
+    require!(covered > 0, VaultError::InvalidAmount);
+
+    let seeds = &[
+        b"vault_state".as_ref(),
+        &[vault_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.jusdi_mint.to_account_info(),
+            from: ctx.accounts.vault_jusdi_account.to_account_info(),
+            authority: vault_state.to_account_info(),
+        },
+        signer,
+    );
+    token::burn(cpi_ctx, covered)?;
+
+    vault_state.bad_debt = vault_state.bad_debt.checked_sub(covered).ok_or(VaultError::MathOverflow)?;
+    vault_state.insurance_balance = vault_state.insurance_balance.checked_sub(covered).ok_or(VaultError::MathOverflow)?;
+
+    emit!(BadDebtCovered {
+        amount: covered,
+        remaining_bad_debt: vault_state.bad_debt,
+    });
+
+    Ok(())
+}
+
+/// Writes off bad debt the reserve can't cover. The loss is spread across all jUSDi
+/// holders, since that supply is no longer backed by any loan.
+pub fn socialize_bad_debt(ctx: Context<ManageBadDebt>, amount: u64) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+    require!(vault_state.insurance_balance == 0, VaultError::InsuranceNotExhausted);
+    require!(vault_state.bad_debt > 0, VaultError::NoBadDebt);
+
+    let socialized = amount.min(vault_state.bad_debt);
+    require!(socialized > 0, VaultError::InvalidAmount);
+
+    vault_state.bad_debt = vault_state.bad_debt.checked_sub(socialized).ok_or(VaultError::MathOverflow)?;
+
+    emit!(BadDebtSocialized {
+        amount: socialized,
+        remaining_bad_debt: vault_state.bad_debt,
+    });
+
+    Ok(())
+}
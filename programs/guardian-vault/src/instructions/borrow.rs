@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use crate::state::{VaultState, UserLoan, PAUSE_BORROW, PRICE_HISTORY_EXPO};
+use crate::events::{LoanBorrowed, CircuitBreakerTripped};
+use crate::errors::VaultError;
+use crate::oracle;
+
+#[derive(Accounts)]
+pub struct Borrow<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_loan", user.key().as_ref()],
+        bump = user_loan.bump,
+        constraint = user_loan.owner == user.key() @ VaultError::Unauthorized
+    )]
+    pub user_loan: Box<Account<'info, UserLoan>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = user
+    )]
+    pub user_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    // Wallet SKR balance decides the holder bonus
+    #[account(
+        associated_token::mint = vault_state.skr_mint,
+        associated_token::authority = user
+    )]
+    pub user_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = vault_state.jusdi_mint @ VaultError::InvalidMint
+    )]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Oracle price account - validated by constraint
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
+    pub skr_price_feed: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<Borrow>, amount: u64) -> Result<()> {
+    require!(!ctx.accounts.vault_state.is_paused(PAUSE_BORROW), VaultError::Paused);
+    require!(!ctx.accounts.vault_state.is_settled(), VaultError::VaultSettled);
+    require!(!ctx.accounts.vault_state.breaker_tripped, VaultError::BorrowHalted);
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault_state.accrue_interest(current_timestamp)?;
+    let borrow_index = ctx.accounts.vault_state.borrow_index;
+    ctx.accounts.user_loan.sync_debt(borrow_index)?;
+
+    // Circuit breaker, as in deposit_skr_and_borrow
+    let spot = oracle::get_skr_price(&ctx.accounts.skr_price_feed.to_account_info(), ctx.remaining_accounts, &ctx.accounts.vault_state, current_timestamp)?;
+    let last_price = ctx.accounts.vault_state.last_price;
+    let spot_price = spot.price_at_expo(PRICE_HISTORY_EXPO)?;
+    if let Some(deviation_bps) = ctx.accounts.vault_state.check_price_move(spot_price, current_timestamp)? {
+        msg!("Price moved {} bps, borrowing halted", deviation_bps);
+        emit!(CircuitBreakerTripped {
+            last_price,
+            price: spot_price,
+            deviation_bps,
+            timestamp: current_timestamp,
+        });
+        return Ok(());
+    }
+
+    let vault_state = &ctx.accounts.vault_state;
+    let user_loan = &ctx.accounts.user_loan;
+
+    // 1. Value existing collateral at the live oracle price, less its confidence interval
+    let price = spot.lower_bound()?;
+    let collateral_value = oracle::collateral_value(user_loan.collateral_amount, vault_state.skr_decimals, &price)?;
+
+    // 2. Check post-borrow LTV against the vault's current LTV params
+    let ltv_bps = vault_state.ltv_for_balance(ctx.accounts.user_skr_account.amount)? as u128;
+    let new_debt = user_loan.debt_amount.checked_add(amount).ok_or(VaultError::MathOverflow)?;
+    let max_debt = collateral_value
+        .checked_mul(ltv_bps).ok_or(VaultError::MathOverflow)?
+        .checked_div(10000).ok_or(VaultError::MathOverflow)?;
+    require!(new_debt as u128 <= max_debt, VaultError::BorrowExceedsLtv);
+
+    // 3. Mint jUSDi
+    let seeds = &[
+        b"vault_state".as_ref(),
+        &[vault_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let mint_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        MintTo {
+            mint: ctx.accounts.jusdi_mint.to_account_info(),
+            to: ctx.accounts.user_jusdi_account.to_account_info(),
+            authority: vault_state.to_account_info(),
+        },
+        signer,
+    );
+    token::mint_to(mint_ctx, amount)?;
+
+    // 4. Update Loan + Global Debt
+    let user_loan = &mut ctx.accounts.user_loan;
+    user_loan.add_debt(amount, borrow_index)?;
+
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.total_debt = vault_state.total_debt.checked_add(amount).ok_or(VaultError::MathOverflow)?;
+
+    // Resulting LTV for indexers (collateral_value is non-zero here, else max_debt would be 0)
+    let resulting_ltv_bps = (user_loan.debt_amount as u128)
+        .checked_mul(10000).ok_or(VaultError::MathOverflow)?
+        .checked_div(collateral_value).ok_or(VaultError::MathOverflow)? as u16;
+
+    emit!(LoanBorrowed {
+        user: user_loan.owner,
+        amount,
+        total_debt: new_debt,
+        ltv_bps: resulting_ltv_bps,
+    });
+
+    Ok(())
+}
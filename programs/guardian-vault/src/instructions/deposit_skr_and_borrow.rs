@@ -1,11 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, MintTo};
 use anchor_spl::associated_token::AssociatedToken;
-#[cfg(not(feature = "devnet"))]
-use pyth_sdk_solana::state::SolanaPriceAccount;
-use crate::state::{VaultState, UserLoan};
-use crate::events::LoanCreated;
+use crate::state::{VaultState, UserLoan, PAUSE_BORROW, PAUSE_DEPOSIT, PRICE_HISTORY_EXPO, YIELD_SOURCE_SIMULATED, YIELD_SOURCE_STAKE_POOL};
+use crate::events::{LoanCreated, CircuitBreakerTripped};
 use crate::errors::VaultError;
+use crate::oracle;
+use crate::stake_pool;
 
 #[derive(Accounts)]
 pub struct DepositSkrAndBorrow<'info> {
@@ -55,86 +55,62 @@ pub struct DepositSkrAndBorrow<'info> {
     pub user_skr_account: Box<Account<'info, TokenAccount>>,
     
     // The mint of the collateral (mock SKR)
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
     pub mock_skr_mint: Box<Account<'info, Mint>>,
 
     // CRITICAL-03 FIX: Validate vault is mint authority
     #[account(
         mut,
-        constraint = jusdi_mint.mint_authority.unwrap() == vault_state.key() @ VaultError::InvalidMintAuthority
+        address = vault_state.jusdi_mint @ VaultError::InvalidMint
     )]
     pub jusdi_mint: Box<Account<'info, Mint>>,
 
     /// CHECK: Pyth price feed account - validated in handler
-    #[account(constraint = skr_price_feed.key() == vault_state.skr_price_feed @ VaultError::InvalidPriceFeed)]
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
     pub skr_price_feed: UncheckedAccount<'info>,
 
+    /// CHECK: Stake pool issuing the collateral - validated by constraint, parsed in handler
+    #[account(constraint = vault_state.yield_source == YIELD_SOURCE_SIMULATED || stake_pool.key() == vault_state.stake_pool @ VaultError::InvalidStakePool)]
+    pub stake_pool: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn handler(ctx: Context<DepositSkrAndBorrow>, skr_amount: u64) -> Result<()> {
-    let vault_state = &ctx.accounts.vault_state;
-    require!(!vault_state.paused, VaultError::Paused);
+    require!(!ctx.accounts.vault_state.is_paused(PAUSE_DEPOSIT | PAUSE_BORROW), VaultError::Paused);
+    require!(!ctx.accounts.vault_state.is_settled(), VaultError::VaultSettled);
+    require!(!ctx.accounts.vault_state.breaker_tripped, VaultError::BorrowHalted);
     
     let current_timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault_state.accrue_interest(current_timestamp)?;
+    let borrow_index = ctx.accounts.vault_state.borrow_index;
+    ctx.accounts.user_loan.sync_debt(borrow_index)?;
+
+    // Circuit breaker: a sharp price move halts borrowing until an admin resets it.
+    // Returns Ok so the halt is saved; nothing is deposited or borrowed.
+    let spot = oracle::get_skr_price(&ctx.accounts.skr_price_feed.to_account_info(), ctx.remaining_accounts, &ctx.accounts.vault_state, current_timestamp)?;
+    let last_price = ctx.accounts.vault_state.last_price;
+    let spot_price = spot.price_at_expo(PRICE_HISTORY_EXPO)?;
+    if let Some(deviation_bps) = ctx.accounts.vault_state.check_price_move(spot_price, current_timestamp)? {
+        msg!("Price moved {} bps, borrowing halted", deviation_bps);
+        emit!(CircuitBreakerTripped {
+            last_price,
+            price: spot_price,
+            deviation_bps,
+            timestamp: current_timestamp,
+        });
+        return Ok(());
+    }
+    let vault_state = &ctx.accounts.vault_state;
     
     // Calculate LTV
-    let user_balance_before = ctx.accounts.user_skr_account.amount;
-    // 100 SKR = 100 * 10^6
-    let bonus_threshold = 100u64.checked_mul(10u64.pow(6)).ok_or(VaultError::MathOverflow)?;
-    
-    let ltv_bps = if user_balance_before >= bonus_threshold {
-        vault_state.base_ltv_bps + vault_state.skr_holder_bonus_bps
-    } else {
-        vault_state.base_ltv_bps
-    };
-    
-    // PYTH ORACLE INTEGRATION (with devnet fallback)
-    #[cfg(feature = "devnet")]
-    let (price_i64, expo) = (10_000_000i64, -6i32); // $10.00 fallback for devnet
-    
-    #[cfg(not(feature = "devnet"))]
-    let (price_i64, expo) = {
-        let price_feed = SolanaPriceAccount::account_info_to_feed(&ctx.accounts.skr_price_feed.to_account_info())
-            .map_err(|_| VaultError::InvalidPriceFeed)?;
-        let price_data = price_feed.get_price_no_older_than(current_timestamp, 60)
-            .ok_or(VaultError::StaleOracle)?;
-        (price_data.price, price_data.expo)
-    };
+    let ltv_bps = vault_state.ltv_for_balance(ctx.accounts.user_skr_account.amount)?;
     
-    require!(price_i64 > 0, VaultError::InvalidPriceFeed);
-    let price_u64 = price_i64 as u64;
-    
-    let _decimals = 6; // Mock SKR and jUSDi
-    
-    // Calculation: collateral_value = (amount * price) * 10^(decimals + expo - decimals) = amount * price * 10^expo
-    // If expo is -8, we divide by 10^8.
-    
-    // Using u128 for intermediate calc
-    let amount_u128 = skr_amount as u128;
-    let price_u128 = price_u64 as u128;
-    let value_u128 = amount_u128.checked_mul(price_u128).ok_or(VaultError::MathOverflow)?;
-    
-    let scale_adj = expo.abs() as u32;
-    // value (raw) needs to be shifted by expo
-    // if expo (-8) -> value is X * 10^-8 USD.
-    // We want jUSDi amount (6 decimals).
-    // So output should be USD value * 10^6.
-    
-    // target_value = value * 10^6 * 10^expo
-    
-    let borrow_amount_full_ltv = if expo < 0 {
-        // value_u128 / 10^abs(expo)
-        // This assumes source_decimals == target_decimals (6).
-        // If they differed, we'd adjust by 10^(target - source).
-        let den = 10u128.pow(scale_adj);
-        value_u128.checked_div(den).ok_or(VaultError::MathOverflow)?
-    } else {
-        // value_u128 * 10^expo
-        let factor = 10u128.pow(expo as u32);
-        value_u128.checked_mul(factor).ok_or(VaultError::MathOverflow)?
-    };
+    // Value the deposit at the oracle price less its confidence interval (USD, 6 decimals)
+    let price = spot.lower_bound()?;
+    let borrow_amount_full_ltv = oracle::collateral_value(skr_amount, vault_state.skr_decimals, &price)?;
     
     // Apply LTV
     // amount = full_val * ltv_bps / 10000
@@ -142,7 +118,7 @@ pub fn handler(ctx: Context<DepositSkrAndBorrow>, skr_amount: u64) -> Result<()>
         .checked_mul(ltv_bps as u128).ok_or(VaultError::MathOverflow)?
         .checked_div(10000u128).ok_or(VaultError::MathOverflow)?;
         
-    let borrow_amount_u64 = borrow_amount as u64;
+    let borrow_amount_u64 = u64::try_from(borrow_amount).map_err(|_| VaultError::MathOverflow)?;
 
     // 4. Transfer SKR
     let cpi_accounts = Transfer {
@@ -175,9 +151,16 @@ pub fn handler(ctx: Context<DepositSkrAndBorrow>, skr_amount: u64) -> Result<()>
     // 6. Update Loan State
     let user_loan = &mut ctx.accounts.user_loan;
     // user_loan.owner set in init
+    let collateral_before = user_loan.collateral_amount;
     user_loan.collateral_amount = user_loan.collateral_amount.checked_add(skr_amount).ok_or(VaultError::MathOverflow)?;
-    user_loan.debt_amount = user_loan.debt_amount.checked_add(borrow_amount_u64).ok_or(VaultError::MathOverflow)?;
+    user_loan.add_debt(borrow_amount_u64, borrow_index)?;
     user_loan.last_harvest = current_timestamp;
+    // New tokens join at today's exchange rate; blending the baselines keeps the gain the
+    // existing collateral hasn't harvested yet
+    if ctx.accounts.vault_state.yield_source == YIELD_SOURCE_STAKE_POOL {
+        let rate = stake_pool::current_exchange_rate(&ctx.accounts.stake_pool, &ctx.accounts.vault_state.skr_mint, Clock::get()?.epoch)?;
+        user_loan.last_exchange_rate = stake_pool::blended_rate(collateral_before, user_loan.last_exchange_rate, skr_amount, rate)?;
+    }
     
     // Set LTV snapshot if this is fresh
     if user_loan.collateral_amount == skr_amount {
@@ -223,6 +206,7 @@ pub fn init_user_loan(ctx: Context<InitUserLoan>) -> Result<()> {
     user_loan.created_at = Clock::get()?.unix_timestamp;
     user_loan.bump = *ctx.bumps.get("user_loan").unwrap();
     user_loan.debt_amount = 0;
+    user_loan.scaled_debt = 0;
     user_loan.collateral_amount = 0;
     Ok(())
 }
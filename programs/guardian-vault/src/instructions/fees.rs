@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use crate::state::VaultState;
+use crate::events::FeesCollected;
+use crate::errors::VaultError;
+
+// Stability fees grow `total_debt` without minting anything, so until they are collected
+// the outstanding jUSDi falls short of what borrowers owe. Collecting mints the gap to
+// the treasury; anyone can crank it since the destination is fixed.
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(address = vault_state.labs_treasury)]
+    pub labs_treasury_skr: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = jusdi_mint,
+        constraint = treasury_jusdi_account.owner == labs_treasury_skr.owner @ VaultError::InvalidTreasuryAccount
+    )]
+    pub treasury_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = vault_state.jusdi_mint @ VaultError::InvalidMint
+    )]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let amount = vault_state.take_accrued_fees(current_timestamp)?;
+    require!(amount > 0, VaultError::NoFeesToCollect);
+
+    let seeds = &[
+        b"vault_state".as_ref(),
+        &[vault_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        MintTo {
+            mint: ctx.accounts.jusdi_mint.to_account_info(),
+            to: ctx.accounts.treasury_jusdi_account.to_account_info(),
+            authority: vault_state.to_account_info(),
+        },
+        signer,
+    );
+    token::mint_to(cpi_ctx, amount)?;
+
+    emit!(FeesCollected {
+        amount,
+        treasury_account: ctx.accounts.treasury_jusdi_account.key(),
+        total_debt: vault_state.total_debt,
+    });
+
+    Ok(())
+}
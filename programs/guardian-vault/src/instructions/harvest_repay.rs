@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, MintTo};
-use crate::state::{VaultState, UserLoan};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, MintTo, Transfer};
+use crate::state::{VaultState, UserLoan, PAUSE_HARVEST, YIELD_SOURCE_SIMULATED, YIELD_SOURCE_STAKE_POOL};
 use crate::events::RewardHarvested;
 use crate::errors::VaultError;
+use crate::oracle;
+use crate::stake_pool;
 
 #[derive(Accounts)]
 pub struct HarvestRepay<'info> {
@@ -43,150 +45,195 @@ pub struct HarvestRepay<'info> {
     )]
     pub vault_jusdi_account: Box<Account<'info, TokenAccount>>,
 
-    #[account(mut)]
+    #[account(mut, address = vault_state.jusdi_mint @ VaultError::InvalidMint)]
     pub jusdi_mint: Box<Account<'info, Mint>>,
     
+    // Simulated harvests mint the fee from it
+    #[account(mut, address = vault_state.skr_mint @ VaultError::InvalidMint)]
     pub mock_skr_mint: Account<'info, Mint>,
 
+    /// CHECK: Oracle price account - validated by constraint
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
+    pub skr_price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Stake pool issuing the collateral - validated by constraint, parsed in handler
+    #[account(constraint = vault_state.yield_source == YIELD_SOURCE_SIMULATED || stake_pool.key() == vault_state.stake_pool @ VaultError::InvalidStakePool)]
+    pub stake_pool: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
 }
 
+/// Outcome of one harvest, in SKR base units except `debt_reduction` (jUSDi).
+struct Harvest {
+    rewards_earned: u64,
+    fee_amount: u64,
+    /// Collateral tokens taken out of the loan
+    collateral_used: u64,
+    debt_reduction: u64,
+    /// Stake pool exchange rate to record on the loan (0 when not applicable)
+    exchange_rate: u128,
+    /// Simulated APY used (0 when not applicable)
+    reward_rate_bps_per_year: u16,
+}
+
 pub fn handler(ctx: Context<HarvestRepay>) -> Result<()> {
     // CRITICAL-02 FIX: Check pause state
-    let vault_state = &mut ctx.accounts.vault_state;
-    require!(!vault_state.paused, VaultError::Paused);
+    require!(!ctx.accounts.vault_state.is_paused(PAUSE_HARVEST), VaultError::Paused);
+    require!(!ctx.accounts.vault_state.is_settled(), VaultError::VaultSettled);
 
-    // 1. Calculate Rewards
-    let user_loan = &mut ctx.accounts.user_loan;
     let current_timestamp = Clock::get()?.unix_timestamp;
-    
-    let time_elapsed = current_timestamp.checked_sub(user_loan.last_harvest).unwrap_or(0);
-    if time_elapsed <= 0 {
+    ctx.accounts.vault_state.accrue_interest(current_timestamp)?;
+    let borrow_index = ctx.accounts.vault_state.borrow_index;
+    ctx.accounts.user_loan.sync_debt(borrow_index)?;
+
+    // 1. Calculate rewards and pay the fee
+    let harvest = match ctx.accounts.vault_state.yield_source {
+        YIELD_SOURCE_STAKE_POOL => harvest_stake_pool(&ctx)?,
+        _ => harvest_simulated(&ctx, current_timestamp)?,
+    };
+
+    let user_loan = &mut ctx.accounts.user_loan;
+    user_loan.last_harvest = current_timestamp;
+    if harvest.exchange_rate > 0 {
+        user_loan.last_exchange_rate = harvest.exchange_rate;
+    }
+    if harvest.rewards_earned == 0 {
         return Ok(());
     }
 
-    // Devnet Simulation: 7% APY
-    // rewards = collateral * 0.07 * (time_elapsed / 31536000)
-    // Calculation: collateral * 7 * time / (100 * 31536000)
-    
-    let _seconds_per_year = 31536000u128; // 365 * 24 * 3600
-    let collateral_u128 = user_loan.collateral_amount as u128;
-    let time_u128 = time_elapsed as u128;
-    
-    // rewards = collateral * 7 * time / 100 / seconds_per_year
-    // rewards = (collateral * 7 * time) / (3153600000)
-    
-    // HIGH-02 FIX: Replace unwrap() with ok_or()
-    let rewards_earned_u128 = collateral_u128
-        .checked_mul(7).ok_or(VaultError::MathOverflow)?
-        .checked_mul(time_u128).ok_or(VaultError::MathOverflow)?
-        .checked_div(3153600000).ok_or(VaultError::MathOverflow)?;
-        
-    let rewards_earned = rewards_earned_u128 as u64;
-    
+    // 2. Spend the harvested collateral
+    let vault_state = &mut ctx.accounts.vault_state;
+    user_loan.collateral_amount = user_loan.collateral_amount.checked_sub(harvest.collateral_used).ok_or(VaultError::MathOverflow)?;
+    vault_state.total_collateral = vault_state.total_collateral.checked_sub(harvest.collateral_used).ok_or(VaultError::MathOverflow)?;
+
+    // 3. Reduce Debt
+    let actual_reduction = harvest.debt_reduction.min(user_loan.debt_amount);
+    user_loan.reduce_debt(actual_reduction, borrow_index)?;
+
+    // Update Global Debt
+    vault_state.reduce_total_debt(actual_reduction);
+
+    emit!(RewardHarvested {
+        user: user_loan.owner,
+        rewards_earned: harvest.rewards_earned,
+        fee_taken: harvest.fee_amount,
+        debt_reduced: actual_reduction,
+        reward_rate_bps_per_year: harvest.reward_rate_bps_per_year,
+    });
+
+    Ok(())
+}
+
+/// Devnet simulation: `reward_rate_bps_per_year` APY on collateral, with the fee minted as
+/// mock SKR.
+fn harvest_simulated(ctx: &Context<HarvestRepay>, current_timestamp: i64) -> Result<Harvest> {
+    // Simulated rewards are unbacked, so they never run outside devnet
+    require!(cfg!(feature = "devnet"), VaultError::DevnetOnly);
+
+    let vault_state = &ctx.accounts.vault_state;
+    let user_loan = &ctx.accounts.user_loan;
+    let mut harvest = Harvest {
+        rewards_earned: 0,
+        fee_amount: 0,
+        collateral_used: 0,
+        debt_reduction: 0,
+        exchange_rate: 0,
+        reward_rate_bps_per_year: vault_state.reward_rate_bps_per_year,
+    };
+
+    let time_elapsed = current_timestamp.checked_sub(user_loan.last_harvest).unwrap_or(0);
+    harvest.rewards_earned = vault_state.simulated_reward(user_loan.collateral_amount, time_elapsed)?;
+    if harvest.rewards_earned == 0 {
+        return Ok(harvest);
+    }
+
+    // Take Fee
+    let fee_bps = vault_state.harvest_fee_bps as u64;
+    harvest.fee_amount = harvest.rewards_earned.checked_mul(fee_bps).ok_or(VaultError::MathOverflow)?.checked_div(10000).ok_or(VaultError::MathOverflow)?;
+    let net_rewards = harvest.rewards_earned.checked_sub(harvest.fee_amount).ok_or(VaultError::MathOverflow)?;
+
+    // MEDIUM-05 FIX: Value the net rewards at the live oracle price (less confidence) in jUSDi units
+    let price = oracle::get_skr_price(&ctx.accounts.skr_price_feed.to_account_info(), ctx.remaining_accounts, vault_state, current_timestamp)?.lower_bound()?;
+    let debt_reduction = oracle::collateral_value(net_rewards, vault_state.skr_decimals, &price)?;
+    harvest.debt_reduction = u64::try_from(debt_reduction).map_err(|_| VaultError::MathOverflow)?;
+
+    // Mint Fee to Treasury (simulates the fee payout)
+    if harvest.fee_amount > 0 {
+        let seeds = &[
+            b"vault_state".as_ref(),
+            &[vault_state.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mock_skr_mint.to_account_info(),
+                to: ctx.accounts.labs_treasury_skr.to_account_info(),
+                authority: vault_state.to_account_info(),
+            },
+            signer,
+        );
+        token::mint_to(cpi_ctx, harvest.fee_amount)?;
+    }
+
+    Ok(harvest)
+}
+
+/// Rewards are the growth in stake backing the loan's pool tokens since the last
+/// harvest. The pool tokens already carry that gain, so it stays in the loan as
+/// collateral, where it lowers the LTV; only the fee share is taken out and sent to the
+/// treasury. No SKR is minted, and no debt is written off without jUSDi being burned.
+fn harvest_stake_pool(ctx: &Context<HarvestRepay>) -> Result<Harvest> {
+    let vault_state = &ctx.accounts.vault_state;
+    let user_loan = &ctx.accounts.user_loan;
+
+    let rate = stake_pool::current_exchange_rate(&ctx.accounts.stake_pool, &vault_state.skr_mint, Clock::get()?.epoch)?;
+    // Only growth above the best rate seen counts, so recovering from a slashing dip
+    // is not paid out twice
+    let mut harvest = Harvest {
+        rewards_earned: 0,
+        fee_amount: 0,
+        collateral_used: 0,
+        debt_reduction: 0,
+        exchange_rate: rate.max(user_loan.last_exchange_rate),
+        reward_rate_bps_per_year: 0,
+    };
+    if user_loan.last_exchange_rate == 0 {
+        return Ok(harvest);
+    }
+
+    let rewards_earned = stake_pool::tokens_for_gain(user_loan.collateral_amount, user_loan.last_exchange_rate, rate)?;
     if rewards_earned == 0 {
-        user_loan.last_harvest = current_timestamp;
-        return Ok(());
+        return Ok(harvest);
     }
 
-    // 2. Take Fee
+    // Take Fee
     let fee_bps = vault_state.harvest_fee_bps as u64;
     let fee_amount = rewards_earned.checked_mul(fee_bps).ok_or(VaultError::MathOverflow)?.checked_div(10000).ok_or(VaultError::MathOverflow)?;
-    let net_rewards = rewards_earned.checked_sub(fee_amount).ok_or(VaultError::MathOverflow)?;
-
-    // 3. "Swap" SKR to jUSDi/USDC to repay debt
-    // On Devnet, we assume 1 Mock SKR = $X. 
-    // And burn jUSDi debt equivalent to net_rewards value?
-    // Wait, the rewards are in SKR (staking rewards).
-    // We need to convert SKR rewards to jUSDi to burn debt.
-    // Or we assume the vault "Sells" SKR rewards for jUSDi (which it burns).
-    // Logic:
-    // Vault Mint newly minted SKR rewards? No, staking increases balance.
-    // In LST, the balance increases or exchange rate changes. 
-    // Here we simulate "rewards_earned" as new tokens? 
-    // Let's assume we mint Mock SKR to vault to simulate rewards accumulation?
-    // Or just assume vault has SKR buffer?
-    // Simpler: Just reduce debt based on calculated value.
-    // Assume 1 SKR = $10 (fixed for simulation or fetch oracle).
-    
-    // Fetch oracle? Not passed in this instruction.
-    // Let's assume passed validation earlier or use cached Pyth?
-    // For Devnet Hackathon, purely simulated:
-    // Assume 1 SKR = 10 jUSDi.
-    // debt_reduction = net_rewards * 10.
-    
-    // Better: Require oracle account to precise calculation.
-    // For now, let's use a fixed rate of $10 for harvest simulation to save complexity, 
-    // or add Pyth account to context.
-    // I'll skip oracle for harvest_repay in this pass to keep it simple, or user can update.
-    // MEDIUM-05: Devnet simulation — $10/SKR. TODO(MAINNET): Replace with Pyth oracle lookup.
-    let simulated_price = 10u64;
-    let debt_reduction = net_rewards.checked_mul(simulated_price).ok_or(VaultError::MathOverflow)?;
-    
-    // Burn jUSDi from vault (assuming vault has some or we just reduce the user debt number?)
-    // Real flow: Swap SKR -> USDC, Buy jUSDi -> Burn.
-    // Here: Update UserLoan debt.
-    // But we need to account for the "fee" transfer.
-    
-    // Transfer fee (SKR) from Vault to Treasury?
-    // Assuming the vault has these SKR tokens (rewards).
-    // Implementation details: "Simulate ... rewards".
-    // We will Mint new Mock SKR to vault to represent rewards? 
-    // No, logic says "Transfer fee to labs_treasury_skr".
-    // This implies Vault has these tokens.
-    // But we haven't increased Vault's SKR balance.
-    // So "harvest" in Mock scenario implies Minting new SKR?
-    // `mint_mock_skr` mints to USER.
-    // `harvest` should probably Mint rewards to Vault first?
-    // Or just skip token transfers and only update accounting for hackathon?
-    // "Transfer fee to treasury" is explicit spec.
-    // So I need to Mint `fee_amount` SKR to Treasury?
-    // And Mint `net_rewards` SKR to Vault (which is then swapped/burned).
-    
-    // Simplified Hackathon Logic:
-    // 1. Mint `fee_amount` Mock SKR to Treasury (Simulate fee payout).
-    // 2. Reduce user debt by `debt_reduction`.
-    // 3. Emit event.
-    
-    // We accept `mock_skr_mint` in context.
-    // We can Mint to Treasury.
-    
-    let seeds = &[
-        b"vault_state".as_ref(),
-        &[vault_state.bump],
-    ];
-    let signer = &[&seeds[..]];
 
-    // Mint Fee to Treasury
+    // Pay the fee from the harvested pool tokens
     if fee_amount > 0 {
-         let cpi_ctx = CpiContext::new_with_signer(
+        let seeds = &[
+            b"vault_state".as_ref(),
+            &[vault_state.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            MintTo {
-                mint: ctx.accounts.mock_skr_mint.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_skr_account.to_account_info(),
                 to: ctx.accounts.labs_treasury_skr.to_account_info(),
                 authority: vault_state.to_account_info(),
             },
             signer,
         );
-        token::mint_to(cpi_ctx, fee_amount)?;
+        token::transfer(cpi_ctx, fee_amount)?;
     }
-    
-    // Reduce Debt
-    let new_debt = user_loan.debt_amount.checked_sub(debt_reduction).unwrap_or(0);
-    let start_debt = user_loan.debt_amount;
-    user_loan.debt_amount = new_debt;
-    user_loan.last_harvest = current_timestamp;
 
-    // Update Global Debt
-    let actual_reduction = start_debt.checked_sub(new_debt).unwrap();
-    vault_state.total_debt = vault_state.total_debt.checked_sub(actual_reduction).unwrap_or(0);
-    
-    emit!(RewardHarvested {
-        user: user_loan.owner,
-        rewards_earned,
-        fee_taken: fee_amount,
-        debt_reduced: actual_reduction,
-    });
-
-    Ok(())
+    harvest.rewards_earned = rewards_earned;
+    harvest.fee_amount = fee_amount;
+    harvest.collateral_used = fee_amount;
+    Ok(harvest)
 }
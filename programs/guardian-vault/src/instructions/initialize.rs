@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::Mint;
 use crate::state::*;
 use crate::errors::VaultError;
 
@@ -18,6 +20,13 @@ pub struct Initialize<'info> {
 
     /// CHECK: The treasury address where harvest fees are sent
     pub labs_treasury: UncheckedAccount<'info>,
+
+    /// SKR collateral mint; its decimals are used to value collateral
+    pub skr_mint: Account<'info, Mint>,
+
+    /// jUSDi mint; the vault must already be its mint authority
+    #[account(constraint = jusdi_mint.mint_authority == COption::Some(vault_state.key()) @ VaultError::InvalidMintAuthority)]
+    pub jusdi_mint: Account<'info, Mint>,
     
     pub system_program: Program<'info, System>,
 }
@@ -31,30 +40,55 @@ pub fn handler(
     liquidation_threshold_bps: u16,
     liquidation_penalty_bps: u16,
 ) -> Result<()> {
-    // HIGH-01 FIX: Validate parameters
-    require!(base_ltv_bps <= 8000, VaultError::LTVTooHigh); // Max 80% base LTV
-    require!(base_ltv_bps + skr_holder_bonus_bps <= 9000, VaultError::LTVTooHigh); // Max 90% with bonus
-    require!(liquidation_threshold_bps > base_ltv_bps + skr_holder_bonus_bps, VaultError::InvalidThreshold);
-    require!(liquidation_threshold_bps <= 9500, VaultError::InvalidThreshold); // Max 95%
-    require!(harvest_fee_bps <= 2000, VaultError::CommissionTooHigh); // Max 20% fee
-    require!(liquidation_penalty_bps <= 1500, VaultError::CommissionTooHigh); // Max 15% penalty
-    require!(cooldown_period >= 0, VaultError::GenericError); // Non-negative cooldown
+    let params = RiskParams {
+        harvest_fee_bps,
+        base_ltv_bps,
+        skr_holder_bonus_bps,
+        cooldown_period,
+        liquidation_threshold_bps,
+        liquidation_penalty_bps,
+    };
+    params.validate()?;
 
     let vault_state = &mut ctx.accounts.vault_state;
     vault_state.authority = ctx.accounts.authority.key();
+    vault_state.pending_authority = Pubkey::default();
+    vault_state.roles = RoleRegistry::default();
     vault_state.labs_treasury = ctx.accounts.labs_treasury.key();
-    vault_state.harvest_fee_bps = harvest_fee_bps;
-    vault_state.base_ltv_bps = base_ltv_bps;
-    vault_state.skr_holder_bonus_bps = skr_holder_bonus_bps;
-    vault_state.cooldown_period = cooldown_period;
-    vault_state.liquidation_threshold_bps = liquidation_threshold_bps;
-    vault_state.liquidation_penalty_bps = liquidation_penalty_bps;
-    vault_state.paused = false;
+    vault_state.skr_mint = ctx.accounts.skr_mint.key();
+    vault_state.skr_decimals = ctx.accounts.skr_mint.decimals;
+    vault_state.jusdi_mint = ctx.accounts.jusdi_mint.key();
+    vault_state.set_risk_params(&params);
+    vault_state.yield_source = YIELD_SOURCE_SIMULATED;
+    vault_state.stake_pool = Pubkey::default();
+    vault_state.reward_rate_bps_per_year = DEFAULT_REWARD_RATE_BPS_PER_YEAR;
+    vault_state.max_harvest_reward = 0;
+    vault_state.close_factor_bps = DEFAULT_CLOSE_FACTOR_BPS;
+    vault_state.liquidation_mode = LIQUIDATION_MODE_FIXED;
+    vault_state.pause_flags = 0;
+    vault_state.max_oracle_staleness_secs = DEFAULT_MAX_ORACLE_STALENESS_SECS;
+    vault_state.max_confidence_bps = DEFAULT_MAX_CONFIDENCE_BPS;
+    vault_state.oracle_sources = [OracleSource::default(); MAX_ORACLE_SOURCES];
+    vault_state.min_oracle_sources = 1;
+    vault_state.twap_window_secs = DEFAULT_TWAP_WINDOW_SECS;
+    vault_state.last_price = 0;
+    vault_state.last_price_at = 0;
+    vault_state.max_price_deviation_bps = DEFAULT_MAX_PRICE_DEVIATION_BPS;
+    vault_state.price_deviation_window_secs = DEFAULT_PRICE_DEVIATION_WINDOW_SECS;
+    vault_state.breaker_tripped = false;
+    vault_state.timelock_delay = 0;
+    vault_state.settled_at = 0;
     vault_state.bump = *ctx.bumps.get("vault_state").unwrap();
     
     // Default values for counters
     vault_state.total_collateral = 0;
     vault_state.total_debt = 0;
+    vault_state.bad_debt = 0;
+    vault_state.insurance_balance = 0;
+    vault_state.borrow_index = INDEX_SCALE;
+    vault_state.stability_fee_per_sec = 0;
+    vault_state.last_accrual = Clock::get()?.unix_timestamp;
+    vault_state.accrued_fees = 0;
 
     Ok(())
 }
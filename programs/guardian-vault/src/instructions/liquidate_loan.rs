@@ -1,10 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, Burn};
-#[cfg(not(feature = "devnet"))]
-use pyth_sdk_solana::state::SolanaPriceAccount;
-use crate::state::{VaultState, UserLoan};
+use crate::state::{VaultState, UserLoan, PriceHistory, LIQUIDATION_DUST_THRESHOLD, LIQUIDATION_MODE_FIXED, PAUSE_LIQUIDATE, PRICE_HISTORY_EXPO};
 use crate::events::LoanLiquidated;
 use crate::errors::VaultError;
+use crate::oracle::{self, OraclePrice};
 
 #[derive(Accounts)]
 pub struct LiquidateLoan<'info> {
@@ -14,8 +13,7 @@ pub struct LiquidateLoan<'info> {
     #[account(
         mut,
         seeds = [b"user_loan", user_loan.owner.as_ref()],
-        bump = user_loan.bump,
-        close = liquidator // Close if fully liquidated
+        bump = user_loan.bump
     )]
     pub user_loan: Box<Account<'info, UserLoan>>,
 
@@ -40,6 +38,14 @@ pub struct LiquidateLoan<'info> {
     )]
     pub liquidator_skr_account: Box<Account<'info, TokenAccount>>,
 
+    // Surplus collateral beyond debt + penalty is returned here
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = user_loan.owner
+    )]
+    pub user_skr_account: Box<Account<'info, TokenAccount>>,
+
     #[account(
         mut,
         associated_token::mint = jusdi_mint,
@@ -54,64 +60,49 @@ pub struct LiquidateLoan<'info> {
     )]
     pub vault_jusdi_account: Box<Account<'info, TokenAccount>>,
     
-    // HIGH-04 FIX: Validate vault is mint authority (matches deposit instruction)
+    // HIGH-04 FIX: Only the vault's own jUSDi mint (matches deposit instruction)
     #[account(
         mut,
-        constraint = jusdi_mint.mint_authority.unwrap() == vault_state.key() @ VaultError::InvalidMintAuthority
+        address = vault_state.jusdi_mint @ VaultError::InvalidMint
     )]
     pub jusdi_mint: Box<Account<'info, Mint>>,
     
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
     pub mock_skr_mint: Box<Account<'info, Mint>>,
 
-    /// CHECK: Pyth price feed - validated by constraint
-    #[account(constraint = skr_price_feed.key() == vault_state.skr_price_feed @ VaultError::InvalidPriceFeed)]
+    /// CHECK: Oracle price account - validated by constraint
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
     pub skr_price_feed: UncheckedAccount<'info>,
 
+    #[account(
+        seeds = [b"price_history"],
+        bump = price_history.bump
+    )]
+    pub price_history: Box<Account<'info, PriceHistory>>,
+
     pub token_program: Program<'info, Token>,
 }
 
-pub fn handler(ctx: Context<LiquidateLoan>) -> Result<()> {
-    // CRITICAL-02 FIX: Check pause state
-    let vault_state = &mut ctx.accounts.vault_state;
-    require!(!vault_state.paused, VaultError::Paused);
+/// Collateral and debt movements for a single liquidation.
+pub(crate) struct LiquidationAmounts {
+    pub debt_repaid: u64,
+    pub collateral_seized: u64,
+    pub penalty_amount: u64,
+    pub collateral_refunded: u64,
+    pub remaining_debt: u64,
+    pub remaining_collateral: u64,
+}
+
+/// Fails with `LoanHealthy` unless the loan's LTV is at or above the liquidation threshold.
+/// Loans can't be liquidated once the vault is in global settlement.
+pub(crate) fn require_unhealthy(vault_state: &VaultState, user_loan: &UserLoan, price: &OraclePrice) -> Result<()> {
+    require!(!vault_state.is_settled(), VaultError::VaultSettled);
 
-    // 1. Check Health
-    let user_loan = &ctx.accounts.user_loan;
-    
-    // PYTH ORACLE INTEGRATION (with devnet fallback)
-    #[cfg(feature = "devnet")]
-    let (price, expo, _ts) = (10_000_000i64 as u128, -6i32, Clock::get()?.unix_timestamp);
-    
-    #[cfg(not(feature = "devnet"))]
-    let (price, expo, _ts) = {
-        let current_timestamp = Clock::get()?.unix_timestamp;
-        let price_feed = SolanaPriceAccount::account_info_to_feed(&ctx.accounts.skr_price_feed.to_account_info())
-            .map_err(|_| VaultError::InvalidPriceFeed)?;
-        let price_data = price_feed.get_price_no_older_than(current_timestamp, 60)
-            .ok_or(VaultError::StaleOracle)?;
-        (price_data.price as u128, price_data.expo, current_timestamp)
-    };
-    
     // LTV Calculation
-    let collateral = user_loan.collateral_amount as u128;
     let debt = user_loan.debt_amount as u128;
-    
-    // Value (USD 6 decimals)
-    // Val = collateral * price * 10^expo 
-    // Wait, reusing logic from deposit.
-    // Normalized Value = collateral * price * 10^expo (assuming matching decimals/expo adjustment).
-    // Let's assume standard calc:
-    // val_usd = collateral * price * 10^expo
-    
     // HIGH-03 FIX: Replace unwrap() with ok_or() to prevent panics
-    let val_usd = if expo < 0 {
-         collateral.checked_mul(price).ok_or(VaultError::MathOverflow)?
-             .checked_div(10u128.pow(expo.abs() as u32)).ok_or(VaultError::MathOverflow)?
-    } else {
-         collateral.checked_mul(price).ok_or(VaultError::MathOverflow)?
-             .checked_mul(10u128.pow(expo as u32)).ok_or(VaultError::MathOverflow)?
-    };
-    
+    let val_usd = oracle::collateral_value(user_loan.collateral_amount, vault_state.skr_decimals, price)?;
+
     // Health Check
     // Threshold e.g. 8000 (80%).
     // If debt * 10000 / val_usd < 8000 -> Is Healthy (LTV < 80%)
@@ -122,37 +113,96 @@ pub fn handler(ctx: Context<LiquidateLoan>) -> Result<()> {
         >= val_usd.checked_mul(max_ltv).ok_or(VaultError::MathOverflow)?;
     
     require!(is_unhealthy, VaultError::LoanHealthy);
+    Ok(())
+}
 
-    // 2. Liquidate
-    // Liquidator pays Debt
-    // Liquidator gets Collateral (+ Penalty? Or just Collateral covering debt + bonus?)
-    // "Calculate penalty = debt_amount * liquidation_penalty_bps / 10000 (5%)"
-    // "Require liquidator burns (debt_amount + penalty) jUSDi" - Wait, usually liquidator burns Debt, and gets Collateral worth Debt + Penalty?
-    // Plan says: "Require liquidator burns (debt_amount + penalty) jUSDi".
-    // This is weird. Usually liquidator pays X debt, gets Y collateral (where Y > X value).
-    // If liquidator burns Debt+Penalty, they pay MORE?
-    // Let's re-read plan: "Require liquidator burns (debt_amount + penalty) jUSDi... Transfer all collateral_amount to liquidator".
-    // Ah, seizing ALL collateral?
-    // If the loan is underwater, seizing ALL might be fair if debt is covered.
-    // But typically: Repay `amount`, Seize `amount * price * (1+bonus)`.
-    // Plan logic: "Transfer all collateral_amount SKR to liquidator ... Close user_loan PDA".
-    // This is a "Full Liquidation" model where the entire position is closed.
-    // Liquidator pays off the debt. 
-    // Does the liquidator pay `debt` or `debt + penalty`?
-    // Plan: "Require liquidator burns (debt_amount + penalty)".
-    // Why penalty? Usually penalty is profit FOR liquidator (discount on collateral).
-    // If liquidator pays extra, they lose money?
-    // Unless "penalty" here means "liquidator pays debt, but user loses collateral worth debt + penalty".
-    // I will assume standard: Liquidator burns `debt_amount`. Gets `collateral`.
-    // If `collateral value > debt`, liquidator profits.
-    // If `is_unhealthy`, usually `collateral value` is close to `debt value` (e.g. 80% LTV -> 125% Collateralization).
-    // So Liquidator pays 80 USD debt, gets 100 USD collateral. Profit 20 USD.
-    // The "penalty" is implicit in losing the 20 USD equity.
-    
-    // Using simple logic: Liquidator burns `debt_amount`. Takes ALL collateral. Closes loan.
-    // This simplifies math and ensures bad debt is gone.
-    
-    // Burn jUSDi from Liquidator
+/// The recorded TWAP as a price. Every liquidation path checks it as well as the spot
+/// price; anyone can crank `record_price` to keep the latest observation recent.
+pub(crate) fn twap_price(vault_state: &VaultState, price_history: &PriceHistory, now: i64) -> Result<OraclePrice> {
+    let max_age = vault_state.observation_interval()
+        .checked_add(vault_state.max_oracle_staleness_secs as i64).ok_or(VaultError::MathOverflow)?;
+    let twap = price_history
+        .twap(now, vault_state.twap_window_secs, max_age)
+        .ok_or(VaultError::TwapUnavailable)?;
+    Ok(OraclePrice { price: twap, conf: 0, expo: PRICE_HISTORY_EXPO, publish_time: now })
+}
+
+/// Seizes collateral worth `debt_repaid * seize_multiplier_bps / 10000` at the oracle price,
+/// or all of it if the loan can't cover that. Once the debt is cleared, whatever is left
+/// over is the borrower's equity and is refunded to them.
+pub(crate) fn size_liquidation(
+    user_loan: &UserLoan,
+    debt_repaid: u64,
+    seize_multiplier_bps: u128,
+    mint_decimals: u8,
+    price: &OraclePrice,
+) -> Result<LiquidationAmounts> {
+    let debt_repaid = debt_repaid.min(user_loan.debt_amount);
+
+    let seize_value = (debt_repaid as u128)
+        .checked_mul(seize_multiplier_bps).ok_or(VaultError::MathOverflow)?
+        .checked_div(10000).ok_or(VaultError::MathOverflow)?;
+    let collateral_seized = oracle::collateral_for_value(seize_value, mint_decimals, price)?
+        .min(user_loan.collateral_amount);
+    let debt_collateral = oracle::collateral_for_value(debt_repaid as u128, mint_decimals, price)?
+        .min(collateral_seized);
+    let penalty_amount = collateral_seized.checked_sub(debt_collateral).ok_or(VaultError::MathOverflow)?;
+
+    let remaining_debt = user_loan.debt_amount.checked_sub(debt_repaid).ok_or(VaultError::MathOverflow)?;
+    let mut remaining_collateral = user_loan.collateral_amount.checked_sub(collateral_seized).ok_or(VaultError::MathOverflow)?;
+
+    let collateral_refunded = if remaining_debt == 0 { remaining_collateral } else { 0 };
+    remaining_collateral = remaining_collateral.checked_sub(collateral_refunded).ok_or(VaultError::MathOverflow)?;
+
+    Ok(LiquidationAmounts {
+        debt_repaid,
+        collateral_seized,
+        penalty_amount,
+        collateral_refunded,
+        remaining_debt,
+        remaining_collateral,
+    })
+}
+
+pub fn handler(ctx: Context<LiquidateLoan>, repay_amount: u64) -> Result<()> {
+    // CRITICAL-02 FIX: Check pause state
+    let vault_state = &mut ctx.accounts.vault_state;
+    require!(!vault_state.is_paused(PAUSE_LIQUIDATE), VaultError::Paused);
+    require!(vault_state.liquidation_mode == LIQUIDATION_MODE_FIXED, VaultError::WrongLiquidationMode);
+    require!(repay_amount > 0, VaultError::InvalidAmount);
+
+    // 1. Check Health
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    vault_state.accrue_interest(current_timestamp)?;
+    let borrow_index = vault_state.borrow_index;
+    ctx.accounts.user_loan.sync_debt(borrow_index)?;
+    let user_loan = &ctx.accounts.user_loan;
+    let price = oracle::get_skr_price(&ctx.accounts.skr_price_feed.to_account_info(), ctx.remaining_accounts, vault_state, current_timestamp)?;
+    require_unhealthy(vault_state, user_loan, &price)?;
+
+    // The TWAP must agree, so a short price wick can't liquidate a healthy loan
+    require_unhealthy(vault_state, user_loan, &twap_price(vault_state, &ctx.accounts.price_history, current_timestamp)?)?;
+
+    // 2. Size the Liquidation
+    // At most close_factor_bps of the debt can be repaid per call; small loans can be
+    // closed out in one go so they don't get stuck as dust.
+    let max_repay = if user_loan.debt_amount <= LIQUIDATION_DUST_THRESHOLD {
+        user_loan.debt_amount
+    } else {
+        ((user_loan.debt_amount as u128)
+            .checked_mul(vault_state.close_factor_bps as u128).ok_or(VaultError::MathOverflow)?
+            .checked_div(10000).ok_or(VaultError::MathOverflow)?) as u64
+    };
+    let LiquidationAmounts {
+        debt_repaid,
+        collateral_seized,
+        penalty_amount,
+        collateral_refunded,
+        remaining_debt,
+        remaining_collateral,
+    } = size_liquidation(user_loan, repay_amount.min(max_repay), vault_state.penalty_multiplier_bps(), vault_state.skr_decimals, &price)?;
+
+    // 3. Burn jUSDi from Liquidator
     let cpi_ctx = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         Burn {
@@ -161,13 +211,14 @@ pub fn handler(ctx: Context<LiquidateLoan>) -> Result<()> {
             authority: ctx.accounts.liquidator.to_account_info(),
         },
     );
-    token::burn(cpi_ctx, user_loan.debt_amount)?;
+    token::burn(cpi_ctx, debt_repaid)?;
     
     // Reduce Global Debt — HIGH-03 FIX: graceful error handling
-    vault_state.total_debt = vault_state.total_debt.checked_sub(user_loan.debt_amount).ok_or(VaultError::MathOverflow)?;
-    vault_state.total_collateral = vault_state.total_collateral.checked_sub(user_loan.collateral_amount).ok_or(VaultError::MathOverflow)?;
+    let collateral_released = collateral_seized.checked_add(collateral_refunded).ok_or(VaultError::MathOverflow)?;
+    vault_state.reduce_total_debt(debt_repaid);
+    vault_state.total_collateral = vault_state.total_collateral.checked_sub(collateral_released).ok_or(VaultError::MathOverflow)?;
 
-    // Seize Collateral
+    // 4. Seize Collateral
     let seeds = &[
         b"vault_state".as_ref(),
         &[vault_state.bump],
@@ -183,16 +234,46 @@ pub fn handler(ctx: Context<LiquidateLoan>) -> Result<()> {
         },
         signer,
     );
-    token::transfer(transfer_ctx, user_loan.collateral_amount)?;
+    token::transfer(transfer_ctx, collateral_seized)?;
+
+    // Refund Surplus to Borrower
+    if collateral_refunded > 0 {
+        let refund_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_skr_account.to_account_info(),
+                to: ctx.accounts.user_skr_account.to_account_info(),
+                authority: vault_state.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(refund_ctx, collateral_refunded)?;
+    }
+
+    // 5. Update Loan State
+    let user_loan = &mut ctx.accounts.user_loan;
+    user_loan.reduce_debt(debt_repaid, borrow_index)?;
+    user_loan.collateral_amount = remaining_collateral;
     
     emit!(LoanLiquidated {
         user: user_loan.owner,
         liquidator: ctx.accounts.liquidator.key(),
-        collateral_seized: user_loan.collateral_amount,
-        debt_repaid: user_loan.debt_amount,
+        collateral_seized,
+        debt_repaid,
+        penalty_amount,
+        collateral_refunded,
+        remaining_debt,
     });
     
-    // Account closed automatically via `close = liquidator`
+    // 6. Close Account only once the position is fully wound down
+    if user_loan.debt_amount == 0 && user_loan.collateral_amount == 0 {
+        let source_account_info = user_loan.to_account_info();
+        let dest_account_info = ctx.accounts.liquidator.to_account_info();
+
+        let dest_lamports = dest_account_info.lamports();
+        **dest_account_info.lamports.borrow_mut() = dest_lamports.checked_add(source_account_info.lamports()).ok_or(VaultError::MathOverflow)?;
+        **source_account_info.lamports.borrow_mut() = 0;
+    }
     
     Ok(())
 }
@@ -0,0 +1,151 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, Burn};
+use crate::state::{VaultState, UserLoan, PriceHistory, PAUSE_LIQUIDATE};
+use crate::events::BadDebtRecorded;
+use crate::errors::VaultError;
+use crate::oracle;
+use super::liquidate_loan::twap_price;
+
+#[derive(Accounts)]
+pub struct LiquidateUnderwater<'info> {
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_loan", user_loan.owner.as_ref()],
+        bump = user_loan.bump,
+        close = liquidator
+    )]
+    pub user_loan: Box<Account<'info, UserLoan>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = vault_state
+    )]
+    pub vault_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = liquidator
+    )]
+    pub liquidator_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = liquidator
+    )]
+    pub liquidator_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = vault_state.jusdi_mint @ VaultError::InvalidMint
+    )]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
+    pub mock_skr_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Oracle price account - validated by constraint
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
+    pub skr_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"price_history"],
+        bump = price_history.bump
+    )]
+    pub price_history: Box<Account<'info, PriceHistory>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<LiquidateUnderwater>) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+    require!(!vault_state.is_paused(PAUSE_LIQUIDATE), VaultError::Paused);
+    require!(!vault_state.is_settled(), VaultError::VaultSettled);
+
+    // 1. Check the loan is underwater (collateral worth less than debt)
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    vault_state.accrue_interest(current_timestamp)?;
+    ctx.accounts.user_loan.sync_debt(vault_state.borrow_index)?;
+    let user_loan = &ctx.accounts.user_loan;
+    let price = oracle::get_skr_price(&ctx.accounts.skr_price_feed.to_account_info(), ctx.remaining_accounts, vault_state, current_timestamp)?;
+    let collateral_value = oracle::collateral_value(user_loan.collateral_amount, vault_state.skr_decimals, &price)?;
+
+    require!(collateral_value < user_loan.debt_amount as u128, VaultError::LoanNotUnderwater);
+    // The TWAP must agree, so a short price wick can't write off a solvent loan
+    let twap_value = oracle::collateral_value(
+        user_loan.collateral_amount,
+        vault_state.skr_decimals,
+        &twap_price(vault_state, &ctx.accounts.price_history, current_timestamp)?,
+    )?;
+    require!(twap_value < user_loan.debt_amount as u128, VaultError::LoanNotUnderwater);
+
+    // 2. Liquidator buys all collateral for what it is worth, less the liquidation penalty
+    // as their incentive. Anything the payment doesn't cover is bad debt.
+    let debt_repaid = collateral_value
+        .checked_mul(10000).ok_or(VaultError::MathOverflow)?
+        .checked_div(10000u128.checked_add(vault_state.liquidation_penalty_bps as u128).ok_or(VaultError::MathOverflow)?)
+        .ok_or(VaultError::MathOverflow)? as u64;
+    let shortfall = user_loan.debt_amount.checked_sub(debt_repaid).ok_or(VaultError::MathOverflow)?;
+
+    if debt_repaid > 0 {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.jusdi_mint.to_account_info(),
+                from: ctx.accounts.liquidator_jusdi_account.to_account_info(),
+                authority: ctx.accounts.liquidator.to_account_info(),
+            },
+        );
+        token::burn(cpi_ctx, debt_repaid)?;
+    }
+
+    // 3. Seize all Collateral
+    let seeds = &[
+        b"vault_state".as_ref(),
+        &[vault_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    if user_loan.collateral_amount > 0 {
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_skr_account.to_account_info(),
+                to: ctx.accounts.liquidator_skr_account.to_account_info(),
+                authority: vault_state.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, user_loan.collateral_amount)?;
+    }
+
+    // 4. Move the shortfall out of total_debt and into bad_debt
+    vault_state.reduce_total_debt(user_loan.debt_amount);
+    vault_state.total_collateral = vault_state.total_collateral.checked_sub(user_loan.collateral_amount).ok_or(VaultError::MathOverflow)?;
+    vault_state.bad_debt = vault_state.bad_debt.checked_add(shortfall).ok_or(VaultError::MathOverflow)?;
+
+    emit!(BadDebtRecorded {
+        user: user_loan.owner,
+        liquidator: ctx.accounts.liquidator.key(),
+        collateral_seized: user_loan.collateral_amount,
+        debt_repaid,
+        shortfall,
+        total_bad_debt: vault_state.bad_debt,
+    });
+
+    // Account closed automatically via `close = liquidator`
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, Burn};
+use crate::state::{VaultState, UserLoan, StabilityPool, StabilityEpoch, PriceHistory, LIQUIDATION_MODE_FIXED, PAUSE_LIQUIDATE};
+use crate::events::{LoanLiquidated, StabilityEpochEnded};
+use crate::errors::VaultError;
+use crate::oracle;
+use super::liquidate_loan::{require_unhealthy, size_liquidation, twap_price, LiquidationAmounts};
+
+#[derive(Accounts)]
+pub struct LiquidateWithPool<'info> {
+    // Permissionless keeper; receives the loan's rent once it is fully wound down
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_loan", user_loan.owner.as_ref()],
+        bump = user_loan.bump
+    )]
+    pub user_loan: Box<Account<'info, UserLoan>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        seeds = [b"stability_pool"],
+        bump = stability_pool.bump
+    )]
+    pub stability_pool: Box<Account<'info, StabilityPool>>,
+
+    // Snapshot of the current epoch, written if this liquidation empties the pool
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = 8 + std::mem::size_of::<StabilityEpoch>(),
+        seeds = [b"stability_epoch", stability_pool.epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub stability_epoch: Box<Account<'info, StabilityEpoch>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = vault_state
+    )]
+    pub vault_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = stability_pool
+    )]
+    pub pool_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = stability_pool
+    )]
+    pub pool_skr_account: Box<Account<'info, TokenAccount>>,
+
+    // Surplus collateral beyond debt + penalty is returned here
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = user_loan.owner
+    )]
+    pub user_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = vault_state.jusdi_mint @ VaultError::InvalidMint
+    )]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
+    pub mock_skr_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Oracle price account - validated by constraint
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
+    pub skr_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"price_history"],
+        bump = price_history.bump
+    )]
+    pub price_history: Box<Account<'info, PriceHistory>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<LiquidateWithPool>) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+    require!(!vault_state.is_paused(PAUSE_LIQUIDATE), VaultError::Paused);
+    require!(vault_state.liquidation_mode == LIQUIDATION_MODE_FIXED, VaultError::WrongLiquidationMode);
+
+    // 1. Check Health
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    vault_state.accrue_interest(current_timestamp)?;
+    let borrow_index = vault_state.borrow_index;
+    ctx.accounts.user_loan.sync_debt(borrow_index)?;
+    let user_loan = &ctx.accounts.user_loan;
+    let price = oracle::get_skr_price(&ctx.accounts.skr_price_feed.to_account_info(), ctx.remaining_accounts, vault_state, current_timestamp)?;
+    require_unhealthy(vault_state, user_loan, &price)?;
+    require_unhealthy(vault_state, user_loan, &twap_price(vault_state, &ctx.accounts.price_history, current_timestamp)?)?;
+
+    // 2. Size the Liquidation
+    // The pool absorbs as much debt as it can in one go
+    let pool_capacity = ctx.accounts.stability_pool.total_deposits;
+    require!(pool_capacity > 0, VaultError::StabilityPoolEmpty);
+
+    let LiquidationAmounts {
+        debt_repaid,
+        collateral_seized,
+        penalty_amount,
+        collateral_refunded,
+        remaining_debt,
+        remaining_collateral,
+    } = size_liquidation(user_loan, user_loan.debt_amount.min(pool_capacity), vault_state.penalty_multiplier_bps(), vault_state.skr_decimals, &price)?;
+
+    // 3. Burn the pool's jUSDi
+    let pool_seeds = &[
+        b"stability_pool".as_ref(),
+        &[ctx.accounts.stability_pool.bump],
+    ];
+    let pool_signer = &[&pool_seeds[..]];
+
+    let burn_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.jusdi_mint.to_account_info(),
+            from: ctx.accounts.pool_jusdi_account.to_account_info(),
+            authority: ctx.accounts.stability_pool.to_account_info(),
+        },
+        pool_signer,
+    );
+    token::burn(burn_ctx, debt_repaid)?;
+
+    let collateral_released = collateral_seized.checked_add(collateral_refunded).ok_or(VaultError::MathOverflow)?;
+    vault_state.reduce_total_debt(debt_repaid);
+    vault_state.total_collateral = vault_state.total_collateral.checked_sub(collateral_released).ok_or(VaultError::MathOverflow)?;
+
+    // 4. Move seized collateral (including the penalty) into the pool for depositors
+    let seeds = &[
+        b"vault_state".as_ref(),
+        &[vault_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.vault_skr_account.to_account_info(),
+            to: ctx.accounts.pool_skr_account.to_account_info(),
+            authority: vault_state.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, collateral_seized)?;
+
+    let stability_epoch = &mut ctx.accounts.stability_epoch;
+    stability_epoch.epoch = ctx.accounts.stability_pool.epoch;
+    stability_epoch.bump = *ctx.bumps.get("stability_epoch").unwrap();
+    if let Some(collateral_per_share) = ctx.accounts.stability_pool.absorb(debt_repaid, collateral_seized)? {
+        stability_epoch.collateral_per_share = collateral_per_share;
+
+        emit!(StabilityEpochEnded {
+            epoch: stability_epoch.epoch,
+            collateral_per_share,
+        });
+    }
+
+    // Refund Surplus to Borrower
+    if collateral_refunded > 0 {
+        let refund_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_skr_account.to_account_info(),
+                to: ctx.accounts.user_skr_account.to_account_info(),
+                authority: vault_state.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(refund_ctx, collateral_refunded)?;
+    }
+
+    // 5. Update Loan State
+    let user_loan = &mut ctx.accounts.user_loan;
+    user_loan.reduce_debt(debt_repaid, borrow_index)?;
+    user_loan.collateral_amount = remaining_collateral;
+
+    emit!(LoanLiquidated {
+        user: user_loan.owner,
+        liquidator: ctx.accounts.stability_pool.key(),
+        collateral_seized,
+        debt_repaid,
+        penalty_amount,
+        collateral_refunded,
+        remaining_debt,
+    });
+
+    // 6. Close Account only once the position is fully wound down
+    if user_loan.debt_amount == 0 && user_loan.collateral_amount == 0 {
+        let source_account_info = user_loan.to_account_info();
+        let dest_account_info = ctx.accounts.keeper.to_account_info();
+
+        let dest_lamports = dest_account_info.lamports();
+        **dest_account_info.lamports.borrow_mut() = dest_lamports.checked_add(source_account_info.lamports()).ok_or(VaultError::MathOverflow)?;
+        **source_account_info.lamports.borrow_mut() = 0;
+    }
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, MintTo};
 use anchor_spl::associated_token::AssociatedToken;
 use crate::state::VaultState;
+use crate::errors::VaultError;
 
 #[derive(Accounts)]
 pub struct MintMockSkr<'info> {
@@ -10,6 +11,7 @@ pub struct MintMockSkr<'info> {
 
     #[account(
         mut,
+        address = vault_state.skr_mint @ VaultError::InvalidMint,
         mint::authority = vault_state,
     )]
     pub mock_skr_mint: Account<'info, Mint>,
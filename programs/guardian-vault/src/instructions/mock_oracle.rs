@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::state::{VaultState, MockPriceFeed, Role};
+use crate::errors::VaultError;
+
+/// Signed by the oracle admin (or the authority). `id` picks one of several mock feeds.
+#[derive(Accounts)]
+#[instruction(id: u8)]
+pub struct SetMockPrice<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        constraint = vault_state.has_role(Role::OracleAdmin, &admin.key()) @ VaultError::Unauthorized
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + std::mem::size_of::<MockPriceFeed>(),
+        seeds = [b"mock_price".as_ref(), &[id]],
+        bump
+    )]
+    pub mock_price_feed: Account<'info, MockPriceFeed>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Writes the mock feed as given. No checks, so tests can post crashed or stale prices.
+pub fn handler(ctx: Context<SetMockPrice>, price: i64, conf: u64, expo: i32, publish_time: i64) -> Result<()> {
+    let mock_price_feed = &mut ctx.accounts.mock_price_feed;
+    mock_price_feed.price = price;
+    mock_price_feed.conf = conf;
+    mock_price_feed.expo = expo;
+    mock_price_feed.publish_time = publish_time;
+    mock_price_feed.bump = *ctx.bumps.get("mock_price_feed").unwrap();
+    Ok(())
+}
@@ -1,17 +1,45 @@
 
 pub mod initialize;
 pub mod mint_mock_skr;
+pub mod mock_oracle;
 pub mod deposit_skr_and_borrow;
 pub mod harvest_repay;
 pub mod withdraw_collateral;
 pub mod liquidate_loan;
 pub mod admin;
+pub mod repay;
+pub mod add_collateral;
+pub mod borrow;
+pub mod withdraw_partial_collateral;
+pub mod liquidate_underwater;
+pub mod bad_debt;
+pub mod stability_pool;
+pub mod liquidate_with_pool;
+pub mod auction;
+pub mod timelock;
+pub mod settlement;
+pub mod price_history;
+pub mod fees;
 
 // Anchor requires glob reexports for macro expansion
 pub use initialize::*;
 pub use mint_mock_skr::*;
+pub use mock_oracle::*;
 pub use deposit_skr_and_borrow::*;
 pub use harvest_repay::*;
 pub use withdraw_collateral::*;
 pub use liquidate_loan::*;
 pub use admin::*;
+pub use repay::*;
+pub use add_collateral::*;
+pub use borrow::*;
+pub use withdraw_partial_collateral::*;
+pub use liquidate_underwater::*;
+pub use bad_debt::*;
+pub use stability_pool::*;
+pub use liquidate_with_pool::*;
+pub use auction::*;
+pub use timelock::*;
+pub use settlement::*;
+pub use price_history::*;
+pub use fees::*;
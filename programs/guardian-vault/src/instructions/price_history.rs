@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use crate::state::{VaultState, PriceHistory, PriceObservation, PRICE_HISTORY_EXPO};
+use crate::errors::VaultError;
+use crate::oracle;
+
+/// Permissionless crank recording the current oracle price into the price history.
+#[derive(Accounts)]
+pub struct RecordPrice<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = 8 + std::mem::size_of::<PriceHistory>(),
+        seeds = [b"price_history"],
+        bump
+    )]
+    pub price_history: Box<Account<'info, PriceHistory>>,
+
+    /// CHECK: Oracle price account - validated by constraint
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
+    pub skr_price_feed: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn record_price(ctx: Context<RecordPrice>) -> Result<()> {
+    let vault_state = &ctx.accounts.vault_state;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    // 1. Space observations out so the buffer spans the whole TWAP window
+    let price_history = &mut ctx.accounts.price_history;
+    if let Some(latest) = price_history.latest() {
+        let elapsed = current_timestamp.saturating_sub(latest.timestamp);
+        require!(elapsed >= vault_state.observation_interval().max(1), VaultError::ObservationTooSoon);
+    }
+
+    // 2. Record the aggregated spot price
+    let price = oracle::get_skr_price(&ctx.accounts.skr_price_feed.to_account_info(), ctx.remaining_accounts, vault_state, current_timestamp)?;
+    price_history.record(PriceObservation {
+        price: price.price_at_expo(PRICE_HISTORY_EXPO)?,
+        timestamp: current_timestamp,
+    });
+    price_history.bump = *ctx.bumps.get("price_history").unwrap();
+
+    Ok(())
+}
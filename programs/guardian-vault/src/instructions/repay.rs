@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+use crate::state::{VaultState, UserLoan, PAUSE_REPAY};
+use crate::events::LoanRepaid;
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct Repay<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_loan", user.key().as_ref()],
+        bump = user_loan.bump,
+        constraint = user_loan.owner == user.key() @ VaultError::Unauthorized
+    )]
+    pub user_loan: Account<'info, UserLoan>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = user
+    )]
+    pub user_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = vault_state.jusdi_mint @ VaultError::InvalidMint
+    )]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<Repay>, amount: u64) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+    require!(!vault_state.is_paused(PAUSE_REPAY), VaultError::Paused);
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    vault_state.accrue_interest(current_timestamp)?;
+    let borrow_index = vault_state.borrow_index;
+
+    let user_loan = &mut ctx.accounts.user_loan;
+    user_loan.sync_debt(borrow_index)?;
+    require!(user_loan.debt_amount > 0, VaultError::NoOutstandingDebt);
+
+    // Never burn more than is owed
+    let repay_amount = amount.min(user_loan.debt_amount);
+
+    // Burn jUSDi from the borrower
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.jusdi_mint.to_account_info(),
+            from: ctx.accounts.user_jusdi_account.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::burn(cpi_ctx, repay_amount)?;
+
+    // Update Loan + Global Debt
+    user_loan.reduce_debt(repay_amount, borrow_index)?;
+    vault_state.reduce_total_debt(repay_amount);
+
+    emit!(LoanRepaid {
+        user: user_loan.owner,
+        amount: repay_amount,
+        remaining_debt: user_loan.debt_amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, Burn};
+use crate::state::{VaultState, UserLoan, ParamChange};
+use crate::events::{SettlementTriggered, LoanSettled, JusdiRedeemed};
+use crate::errors::VaultError;
+use crate::oracle::{self, OraclePrice};
+use super::admin::{apply_now, AdminConfig};
+
+// Global settlement winds the vault down at a frozen SKR price:
+// 1. `trigger_settlement` (timelocked like the other setters) fixes the price and stops
+//    new debt and liquidations.
+// 2. `settle_loan` (permissionless) keeps SKR worth the loan's debt in the vault and
+//    returns the rest to the borrower.
+// 3. `redeem_jusdi` swaps jUSDi for a pro-rata share of the SKR kept from settled loans.
+
+#[derive(Accounts)]
+pub struct SettleLoan<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_loan", user_loan.owner.as_ref()],
+        bump = user_loan.bump,
+        close = owner
+    )]
+    pub user_loan: Box<Account<'info, UserLoan>>,
+
+    /// CHECK: Loan owner; receives surplus collateral and the loan's rent
+    #[account(mut, address = user_loan.owner @ VaultError::Unauthorized)]
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = vault_state
+    )]
+    pub vault_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
+    pub mock_skr_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemJusdi<'info> {
+    #[account(mut)]
+    pub redeemer: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = redeemer
+    )]
+    pub redeemer_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = redeemer
+    )]
+    pub redeemer_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = vault_state
+    )]
+    pub vault_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = vault_state.jusdi_mint @ VaultError::InvalidMint
+    )]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
+    pub mock_skr_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Settles straight away. Once `timelock_delay` is set, settlement must be queued like
+/// any other change.
+pub fn trigger_settlement(ctx: Context<AdminConfig>, price: i64, expo: i32) -> Result<()> {
+    apply_now(&mut ctx.accounts.vault_state, ParamChange::Settlement(price, expo), &[])
+}
+
+pub(crate) fn apply_settlement(vault_state: &mut VaultState, price: i64, expo: i32) -> Result<()> {
+    require!(!vault_state.is_settled(), VaultError::VaultSettled);
+
+    // Book interest up to now, then stop the clock
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    vault_state.accrue_interest(current_timestamp)?;
+    vault_state.stability_fee_per_sec = 0;
+
+    vault_state.settlement_price = price;
+    vault_state.settlement_expo = expo;
+    vault_state.settled_at = current_timestamp;
+
+    emit!(SettlementTriggered {
+        price,
+        expo,
+        total_collateral: vault_state.total_collateral,
+        total_debt: vault_state.total_debt,
+    });
+    Ok(())
+}
+
+pub fn settle_loan(ctx: Context<SettleLoan>) -> Result<()> {
+    let vault_state = &mut ctx.accounts.vault_state;
+    require!(vault_state.is_settled(), VaultError::VaultNotSettled);
+
+    let user_loan = &mut ctx.accounts.user_loan;
+    user_loan.sync_debt(vault_state.borrow_index)?;
+
+    // 1. Keep SKR worth the debt at the settlement price (rounded up), capped at the collateral
+    let price = OraclePrice {
+        price: vault_state.settlement_price,
+        conf: 0,
+        expo: vault_state.settlement_expo,
+        publish_time: vault_state.settled_at,
+    };
+    let decimals = vault_state.skr_decimals;
+    let mut retained = oracle::collateral_for_value(user_loan.debt_amount as u128, decimals, &price)?;
+    if oracle::collateral_value(retained, decimals, &price)? < user_loan.debt_amount as u128 {
+        retained = retained.checked_add(1).ok_or(VaultError::MathOverflow)?;
+    }
+    let retained = retained.min(user_loan.collateral_amount);
+    let returned = user_loan.collateral_amount.checked_sub(retained).ok_or(VaultError::MathOverflow)?;
+
+    // 2. Return the surplus
+    if returned > 0 {
+        let seeds = &[
+            b"vault_state".as_ref(),
+            &[vault_state.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_skr_account.to_account_info(),
+                to: ctx.accounts.owner_skr_account.to_account_info(),
+                authority: vault_state.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, returned)?;
+    }
+
+    // 3. The loan leaves the books; the retained SKR now backs jUSDi redemptions
+    vault_state.total_collateral = vault_state.total_collateral.checked_sub(user_loan.collateral_amount).ok_or(VaultError::MathOverflow)?;
+    vault_state.reduce_total_debt(user_loan.debt_amount);
+
+    emit!(LoanSettled {
+        user: user_loan.owner,
+        debt_amount: user_loan.debt_amount,
+        collateral_retained: retained,
+        collateral_returned: returned,
+    });
+
+    // Account closed automatically via `close = owner`
+
+    Ok(())
+}
+
+pub fn redeem_jusdi(ctx: Context<RedeemJusdi>, amount: u64) -> Result<()> {
+    let vault_state = &ctx.accounts.vault_state;
+    require!(vault_state.is_settled(), VaultError::VaultNotSettled);
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    // 1. Redemption pool: SKR kept from settled loans (everything in the vault beyond
+    // the collateral of loans still open), shared by the jUSDi those loans account for.
+    // Uncollected fees count as outstanding jUSDi, since `total_debt` already includes them.
+    let pool_skr = ctx.accounts.vault_skr_account.amount.saturating_sub(vault_state.total_collateral);
+    let settled_supply = ctx.accounts.jusdi_mint.supply
+        .checked_add(vault_state.accrued_fees).ok_or(VaultError::MathOverflow)?
+        .saturating_sub(vault_state.total_debt);
+    require!(settled_supply > 0 && amount <= settled_supply, VaultError::NothingToRedeem);
+
+    let skr_out = (amount as u128)
+        .checked_mul(pool_skr as u128).ok_or(VaultError::MathOverflow)?
+        .checked_div(settled_supply as u128).ok_or(VaultError::MathOverflow)? as u64;
+    require!(skr_out > 0, VaultError::NothingToRedeem);
+
+    // 2. Burn jUSDi
+    let burn_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.jusdi_mint.to_account_info(),
+            from: ctx.accounts.redeemer_jusdi_account.to_account_info(),
+            authority: ctx.accounts.redeemer.to_account_info(),
+        },
+    );
+    token::burn(burn_ctx, amount)?;
+
+    // 3. Pay out SKR
+    let seeds = &[
+        b"vault_state".as_ref(),
+        &[vault_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.vault_skr_account.to_account_info(),
+            to: ctx.accounts.redeemer_skr_account.to_account_info(),
+            authority: vault_state.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, skr_out)?;
+
+    emit!(JusdiRedeemed {
+        redeemer: ctx.accounts.redeemer.key(),
+        jusdi_burned: amount,
+        skr_received: skr_out,
+    });
+    Ok(())
+}
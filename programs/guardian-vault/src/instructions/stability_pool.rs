@@ -0,0 +1,370 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use crate::state::{self, VaultState, StabilityPool, StabilityDeposit, StabilityEpoch, PAUSE_DEPOSIT, PAUSE_WITHDRAW};
+use crate::events::{StabilityDeposited, StabilityWithdrawn, StabilityCollateralClaimed};
+use crate::errors::VaultError;
+
+// Stability pool: depositors park jUSDi that liquidations burn to cancel debt. Each
+// depositor holds shares; absorbing debt shrinks the jUSDi behind every share, while the
+// seized SKR is credited to all shares through `collateral_per_share`. When the pool is
+// emptied a new epoch starts; depositors from an ended epoch claim their SKR against that
+// epoch's `StabilityEpoch` snapshot and start over with no shares.
+
+#[derive(Accounts)]
+pub struct InitStabilityPool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        has_one = authority
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<StabilityPool>(),
+        seeds = [b"stability_pool"],
+        bump
+    )]
+    pub stability_pool: Account<'info, StabilityPool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StabilityDepositAccounts<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        seeds = [b"stability_pool"],
+        bump = stability_pool.bump
+    )]
+    pub stability_pool: Box<Account<'info, StabilityPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + std::mem::size_of::<StabilityDeposit>(),
+        seeds = [b"stability_deposit", depositor.key().as_ref()],
+        bump
+    )]
+    pub stability_deposit: Box<Account<'info, StabilityDeposit>>,
+
+    /// Snapshot of the deposit's epoch; only needed once that epoch has ended
+    pub stability_epoch: Option<Box<Account<'info, StabilityEpoch>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = depositor
+    )]
+    pub depositor_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = depositor
+    )]
+    pub depositor_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = stability_pool
+    )]
+    pub pool_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = stability_pool
+    )]
+    pub pool_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = vault_state.jusdi_mint @ VaultError::InvalidMint)]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
+    pub mock_skr_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StabilityWithdrawAccounts<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        seeds = [b"stability_pool"],
+        bump = stability_pool.bump
+    )]
+    pub stability_pool: Box<Account<'info, StabilityPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"stability_deposit", depositor.key().as_ref()],
+        bump = stability_deposit.bump,
+        constraint = stability_deposit.owner == depositor.key() @ VaultError::Unauthorized
+    )]
+    pub stability_deposit: Box<Account<'info, StabilityDeposit>>,
+
+    /// Snapshot of the deposit's epoch; only needed once that epoch has ended
+    pub stability_epoch: Option<Box<Account<'info, StabilityEpoch>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = depositor
+    )]
+    pub depositor_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = depositor
+    )]
+    pub depositor_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = jusdi_mint,
+        associated_token::authority = stability_pool
+    )]
+    pub pool_jusdi_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = stability_pool
+    )]
+    pub pool_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = vault_state.jusdi_mint @ VaultError::InvalidMint)]
+    pub jusdi_mint: Box<Account<'info, Mint>>,
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
+    pub mock_skr_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn init_stability_pool(ctx: Context<InitStabilityPool>) -> Result<()> {
+    let stability_pool = &mut ctx.accounts.stability_pool;
+    stability_pool.total_deposits = 0;
+    stability_pool.total_shares = 0;
+    stability_pool.collateral_per_share = 0;
+    stability_pool.epoch = 0;
+    stability_pool.bump = *ctx.bumps.get("stability_pool").unwrap();
+    Ok(())
+}
+
+/// Pays out the depositor's pending SKR gains and returns the amount paid. Shares from an
+/// ended epoch are settled against its snapshot and dropped.
+fn settle_collateral_gain<'info>(
+    stability_pool: &Account<'info, StabilityPool>,
+    stability_deposit: &mut Account<'info, StabilityDeposit>,
+    stability_epoch: Option<&Account<'info, StabilityEpoch>>,
+    pool_skr_account: &Account<'info, TokenAccount>,
+    depositor_skr_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<u64> {
+    let epoch_ended = stability_deposit.epoch != stability_pool.epoch;
+    let collateral_per_share = if epoch_ended {
+        let snapshot = stability_epoch.ok_or(VaultError::StabilityEpochMissing)?;
+        require!(snapshot.epoch == stability_deposit.epoch, VaultError::StabilityEpochMissing);
+        snapshot.collateral_per_share
+    } else {
+        stability_pool.collateral_per_share
+    };
+
+    let accrued = state::accrued_collateral(stability_deposit.shares, collateral_per_share)?;
+    let pending = accrued.checked_sub(stability_deposit.collateral_debt).ok_or(VaultError::MathOverflow)?;
+    let pending = u64::try_from(pending).map_err(|_| VaultError::MathOverflow)?;
+
+    if pending > 0 {
+        let seeds = &[
+            b"stability_pool".as_ref(),
+            &[stability_pool.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: pool_skr_account.to_account_info(),
+                to: depositor_skr_account.to_account_info(),
+                authority: stability_pool.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(cpi_ctx, pending)?;
+    }
+
+    if epoch_ended {
+        stability_deposit.shares = 0;
+        stability_deposit.collateral_debt = 0;
+        stability_deposit.epoch = stability_pool.epoch;
+    } else {
+        stability_deposit.collateral_debt = accrued;
+    }
+    Ok(pending)
+}
+
+pub fn deposit(ctx: Context<StabilityDepositAccounts>, amount: u64) -> Result<()> {
+    require!(!ctx.accounts.vault_state.is_paused(PAUSE_DEPOSIT), VaultError::Paused);
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let stability_deposit = &mut ctx.accounts.stability_deposit;
+    if stability_deposit.owner == Pubkey::default() {
+        stability_deposit.owner = ctx.accounts.depositor.key();
+        stability_deposit.epoch = ctx.accounts.stability_pool.epoch;
+        stability_deposit.bump = *ctx.bumps.get("stability_deposit").unwrap();
+    }
+
+    // 1. Pay out gains earned on the existing shares
+    let collateral_claimed = settle_collateral_gain(
+        &ctx.accounts.stability_pool,
+        stability_deposit,
+        ctx.accounts.stability_epoch.as_deref(),
+        &ctx.accounts.pool_skr_account,
+        &ctx.accounts.depositor_skr_account,
+        &ctx.accounts.token_program,
+    )?;
+
+    // 2. Transfer jUSDi into the pool
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.depositor_jusdi_account.to_account_info(),
+            to: ctx.accounts.pool_jusdi_account.to_account_info(),
+            authority: ctx.accounts.depositor.to_account_info(),
+        },
+    );
+    token::transfer(cpi_ctx, amount)?;
+
+    // 3. Mint shares at the current jUSDi-per-share rate
+    let stability_pool = &mut ctx.accounts.stability_pool;
+    let shares = stability_pool.deposit_to_shares(amount)?;
+    require!(shares > 0, VaultError::InvalidAmount);
+
+    stability_pool.total_shares = stability_pool.total_shares.checked_add(shares).ok_or(VaultError::MathOverflow)?;
+    stability_pool.total_deposits = stability_pool.total_deposits.checked_add(amount).ok_or(VaultError::MathOverflow)?;
+
+    let stability_deposit = &mut ctx.accounts.stability_deposit;
+    stability_deposit.shares = stability_deposit.shares.checked_add(shares).ok_or(VaultError::MathOverflow)?;
+    stability_deposit.collateral_debt = stability_pool.accrued_collateral(stability_deposit.shares)?;
+
+    emit!(StabilityDeposited {
+        depositor: stability_deposit.owner,
+        amount,
+        shares,
+        collateral_claimed,
+    });
+
+    Ok(())
+}
+
+pub fn withdraw(ctx: Context<StabilityWithdrawAccounts>, amount: u64) -> Result<()> {
+    require!(!ctx.accounts.vault_state.is_paused(PAUSE_WITHDRAW), VaultError::Paused);
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    // 1. Pay out gains earned on the existing shares
+    let collateral_claimed = settle_collateral_gain(
+        &ctx.accounts.stability_pool,
+        &mut ctx.accounts.stability_deposit,
+        ctx.accounts.stability_epoch.as_deref(),
+        &ctx.accounts.pool_skr_account,
+        &ctx.accounts.depositor_skr_account,
+        &ctx.accounts.token_program,
+    )?;
+
+    // 2. Burn shares for the requested jUSDi, capped at what the deposit is still worth
+    let stability_pool = &ctx.accounts.stability_pool;
+    let stability_deposit = &ctx.accounts.stability_deposit;
+    let deposit_value = stability_pool.shares_to_deposit(stability_deposit.shares)?;
+    let amount = amount.min(deposit_value);
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let shares = if amount == deposit_value {
+        stability_deposit.shares
+    } else {
+        // Round up so the pool never pays out more than the burned shares are worth
+        (amount as u128)
+            .checked_mul(stability_pool.total_shares).ok_or(VaultError::MathOverflow)?
+            .checked_add(stability_pool.total_deposits as u128 - 1).ok_or(VaultError::MathOverflow)?
+            .checked_div(stability_pool.total_deposits as u128).ok_or(VaultError::MathOverflow)?
+            .min(stability_deposit.shares)
+    };
+
+    let seeds = &[
+        b"stability_pool".as_ref(),
+        &[stability_pool.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.pool_jusdi_account.to_account_info(),
+            to: ctx.accounts.depositor_jusdi_account.to_account_info(),
+            authority: stability_pool.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)?;
+
+    let stability_pool = &mut ctx.accounts.stability_pool;
+    stability_pool.total_shares = stability_pool.total_shares.checked_sub(shares).ok_or(VaultError::MathOverflow)?;
+    stability_pool.total_deposits = stability_pool.total_deposits.checked_sub(amount).ok_or(VaultError::MathOverflow)?;
+
+    let stability_deposit = &mut ctx.accounts.stability_deposit;
+    stability_deposit.shares = stability_deposit.shares.checked_sub(shares).ok_or(VaultError::MathOverflow)?;
+    stability_deposit.collateral_debt = stability_pool.accrued_collateral(stability_deposit.shares)?;
+
+    emit!(StabilityWithdrawn {
+        depositor: stability_deposit.owner,
+        amount,
+        shares,
+        collateral_claimed,
+    });
+
+    Ok(())
+}
+
+pub fn claim_collateral(ctx: Context<StabilityWithdrawAccounts>) -> Result<()> {
+    require!(!ctx.accounts.vault_state.is_paused(PAUSE_WITHDRAW), VaultError::Paused);
+
+    let collateral_claimed = settle_collateral_gain(
+        &ctx.accounts.stability_pool,
+        &mut ctx.accounts.stability_deposit,
+        ctx.accounts.stability_epoch.as_deref(),
+        &ctx.accounts.pool_skr_account,
+        &ctx.accounts.depositor_skr_account,
+        &ctx.accounts.token_program,
+    )?;
+
+    emit!(StabilityCollateralClaimed {
+        depositor: ctx.accounts.stability_deposit.owner,
+        amount: collateral_claimed,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use crate::state::{VaultState, PendingChange, ParamChange};
+use crate::events::{ChangeQueued, ChangeExecuted, ChangeCancelled};
+use crate::errors::VaultError;
+use super::admin::{apply_change, validate_change};
+
+// Once `timelock_delay` is non-zero, every risk, oracle and yield setter, and settlement, is
+// disabled and its change can only go through queue -> (delay) -> execute, giving users
+// time to exit before it lands.
+
+#[derive(Accounts)]
+#[instruction(change: ParamChange)]
+pub struct QueueChange<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        has_one = authority
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<PendingChange>(),
+        seeds = [b"pending_change".as_ref(), &[change.kind()]],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManagePendingChange<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump,
+        has_one = authority
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_change".as_ref(), &[pending_change.change.kind()]],
+        bump = pending_change.bump,
+        close = authority
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+}
+
+pub fn queue_change(ctx: Context<QueueChange>, change: ParamChange) -> Result<()> {
+    // Reject bad values up front rather than after the delay
+    validate_change(&change)?;
+
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let eta = current_timestamp.checked_add(ctx.accounts.vault_state.timelock_delay).ok_or(VaultError::MathOverflow)?;
+
+    let pending_change = &mut ctx.accounts.pending_change;
+    pending_change.change = change;
+    pending_change.queued_at = current_timestamp;
+    pending_change.eta = eta;
+    pending_change.bump = *ctx.bumps.get("pending_change").unwrap();
+
+    emit!(ChangeQueued {
+        change,
+        eta,
+    });
+    Ok(())
+}
+
+pub fn execute_change(ctx: Context<ManagePendingChange>) -> Result<()> {
+    let change = ctx.accounts.pending_change.change;
+    require!(Clock::get()?.unix_timestamp >= ctx.accounts.pending_change.eta, VaultError::TimelockNotElapsed);
+
+    // Oracle feeds, stake pools and the manual price feed are passed in remaining accounts
+    // and checked as they are now
+    apply_change(&mut ctx.accounts.vault_state, change, ctx.remaining_accounts)?;
+
+    emit!(ChangeExecuted {
+        change,
+    });
+
+    // Account closed automatically via `close = authority`
+
+    Ok(())
+}
+
+pub fn cancel_change(ctx: Context<ManagePendingChange>) -> Result<()> {
+    emit!(ChangeCancelled {
+        change: ctx.accounts.pending_change.change,
+    });
+
+    // Account closed automatically via `close = authority`
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
-use crate::state::{VaultState, UserLoan};
+use crate::state::{VaultState, UserLoan, PAUSE_WITHDRAW};
 use crate::events::CollateralWithdrawn;
 use crate::errors::VaultError;
 
@@ -39,6 +39,7 @@ pub struct WithdrawCollateral<'info> {
     )]
     pub user_skr_account: Box<Account<'info, TokenAccount>>,
 
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
     pub mock_skr_mint: Account<'info, Mint>,
 
     pub token_program: Program<'info, Token>,
@@ -48,15 +49,18 @@ pub fn handler(ctx: Context<WithdrawCollateral>) -> Result<()> {
     let vault_state = &mut ctx.accounts.vault_state;
     
     // CRITICAL-02 FIX: Check pause state
-    require!(!vault_state.paused, VaultError::Paused);
+    require!(!vault_state.is_paused(PAUSE_WITHDRAW), VaultError::Paused);
+
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    vault_state.accrue_interest(current_timestamp)?;
 
     let user_loan = &mut ctx.accounts.user_loan;
+    user_loan.sync_debt(vault_state.borrow_index)?;
 
     // 1. Check Debt
     require!(user_loan.debt_amount == 0, VaultError::DebtNotZero);
     
     // 2. Cooldown Logic
-    let current_timestamp = Clock::get()?.unix_timestamp;
     
     if user_loan.unstake_requested_at == 0 {
         // Start Cooldown - user must call again after period expires
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use crate::state::{VaultState, UserLoan, PAUSE_WITHDRAW};
+use crate::events::CollateralWithdrawn;
+use crate::errors::VaultError;
+use crate::oracle;
+
+#[derive(Accounts)]
+pub struct WithdrawPartialCollateral<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_loan", user.key().as_ref()],
+        bump = user_loan.bump,
+        constraint = user_loan.owner == user.key() @ VaultError::Unauthorized
+    )]
+    pub user_loan: Box<Account<'info, UserLoan>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_state"],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = vault_state
+    )]
+    pub vault_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mock_skr_mint,
+        associated_token::authority = user
+    )]
+    pub user_skr_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = vault_state.skr_mint @ VaultError::InvalidMint)]
+    pub mock_skr_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Oracle price account - validated by constraint
+    #[account(constraint = vault_state.has_oracle_source(&skr_price_feed.key()) @ VaultError::InvalidPriceFeed)]
+    pub skr_price_feed: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Rejects the withdrawal if the collateral left behind cannot carry the loan's debt
+/// at the vault's current borrow LTV for `skr_balance`.
+fn check_remaining_ltv(vault_state: &VaultState, user_loan: &UserLoan, amount: u64, skr_balance: u64, price_feed: &AccountInfo, extra_feeds: &[AccountInfo], current_timestamp: i64) -> Result<()> {
+    if user_loan.debt_amount == 0 {
+        return Ok(());
+    }
+
+    let remaining = user_loan.collateral_amount.checked_sub(amount).ok_or(VaultError::MathOverflow)?;
+    let price = oracle::get_skr_price(price_feed, extra_feeds, vault_state, current_timestamp)?.lower_bound()?;
+    let remaining_value = oracle::collateral_value(remaining, vault_state.skr_decimals, &price)?;
+
+    let ltv_bps = vault_state.ltv_for_balance(skr_balance)?;
+    let max_debt = remaining_value
+        .checked_mul(ltv_bps as u128).ok_or(VaultError::MathOverflow)?
+        .checked_div(10000).ok_or(VaultError::MathOverflow)?;
+    require!(user_loan.debt_amount as u128 <= max_debt, VaultError::WithdrawalExceedsLtv);
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<WithdrawPartialCollateral>, amount: u64) -> Result<()> {
+    require!(!ctx.accounts.vault_state.is_paused(PAUSE_WITHDRAW), VaultError::Paused);
+    require!(!ctx.accounts.vault_state.is_settled(), VaultError::VaultSettled);
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault_state.accrue_interest(current_timestamp)?;
+    let borrow_index = ctx.accounts.vault_state.borrow_index;
+
+    let vault_state = &ctx.accounts.vault_state;
+    let user_loan = &mut ctx.accounts.user_loan;
+    user_loan.sync_debt(borrow_index)?;
+    require!(amount <= user_loan.collateral_amount, VaultError::InsufficientCollateral);
+
+    let price_feed = ctx.accounts.skr_price_feed.to_account_info();
+    let skr_balance = ctx.accounts.user_skr_account.amount;
+
+    // 1. Cooldown Logic - the requested portion must wait out the same cooldown as a full withdrawal
+    if user_loan.partial_requested_at == 0 {
+        check_remaining_ltv(vault_state, user_loan, amount, skr_balance, &price_feed, ctx.remaining_accounts, current_timestamp)?;
+
+        user_loan.partial_requested_at = current_timestamp;
+        user_loan.pending_withdrawal = amount;
+        msg!("Cooldown started at {} for {} SKR. Please return after {} seconds.", current_timestamp, amount, vault_state.cooldown_period);
+        return Ok(());
+    }
+
+    let passed = current_timestamp.checked_sub(user_loan.partial_requested_at).unwrap_or(0);
+    if passed < vault_state.cooldown_period {
+        msg!("Cooldown not met. Passed: {}, Required: {}", passed, vault_state.cooldown_period);
+        return Err(VaultError::CooldownNotMet.into());
+    }
+    require!(amount <= user_loan.pending_withdrawal, VaultError::WithdrawalExceedsRequest);
+
+    // 2. Re-check health at the current price
+    check_remaining_ltv(vault_state, user_loan, amount, skr_balance, &price_feed, ctx.remaining_accounts, current_timestamp)?;
+
+    // 3. Withdraw
+    let seeds = &[
+        b"vault_state".as_ref(),
+        &[vault_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.vault_skr_account.to_account_info(),
+            to: ctx.accounts.user_skr_account.to_account_info(),
+            authority: vault_state.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)?;
+
+    // 4. Update Loan + Global State. The loan stays open; a further withdrawal needs a new cooldown.
+    user_loan.collateral_amount = user_loan.collateral_amount.checked_sub(amount).ok_or(VaultError::MathOverflow)?;
+    user_loan.partial_requested_at = 0;
+    user_loan.pending_withdrawal = 0;
+
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.total_collateral = vault_state.total_collateral.checked_sub(amount).ok_or(VaultError::MathOverflow)?;
+
+    emit!(CollateralWithdrawn {
+        user: ctx.accounts.user.key(),
+        amount,
+    });
+
+    Ok(())
+}
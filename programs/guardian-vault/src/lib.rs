@@ -1,10 +1,13 @@
 pub mod errors;
 pub mod events;
 pub mod instructions;
+pub mod oracle;
+pub mod stake_pool;
 pub mod state;
 
 use anchor_lang::prelude::*;
 use instructions::*;
+use state::{RiskParams, ParamChange, Role, OracleSource};
 
 declare_id!("DwuGR9qYkgYUPxR6jZSkAHdv23YPeqaAwxLAG593L1ar");
 
@@ -45,10 +48,37 @@ pub mod guardian_vault {
         }
     }
 
+    /// TEST-ONLY: Sets a mock oracle price. Returns error unless built with `mock-oracle`.
+    #[allow(unused_variables)]
+    pub fn set_mock_price(ctx: Context<SetMockPrice>, id: u8, price: i64, conf: u64, expo: i32, publish_time: i64) -> anchor_lang::Result<()> {
+        #[cfg(not(feature = "mock-oracle"))]
+        {
+            Err(crate::errors::VaultError::MockOracleOnly.into())
+        }
+        #[cfg(feature = "mock-oracle")]
+        {
+            instructions::mock_oracle::handler(ctx, price, conf, expo, publish_time)
+        }
+    }
+
+    /// If the price circuit breaker trips, this succeeds without depositing or borrowing:
+    /// the halt is saved and `CircuitBreakerTripped` is emitted, so check for that event.
+    /// Later borrows fail with `BorrowHalted` until `reset_circuit_breaker`.
     pub fn deposit_skr_and_borrow(ctx: Context<DepositSkrAndBorrow>, skr_amount: u64) -> anchor_lang::Result<()> {
          instructions::deposit_skr_and_borrow::handler(ctx, skr_amount)
     }
 
+    pub fn add_collateral(ctx: Context<AddCollateral>, skr_amount: u64) -> anchor_lang::Result<()> {
+        instructions::add_collateral::handler(ctx, skr_amount)
+    }
+
+    /// If the price circuit breaker trips, this succeeds without borrowing:
+    /// the halt is saved and `CircuitBreakerTripped` is emitted, so check for that event.
+    /// Later borrows fail with `BorrowHalted` until `reset_circuit_breaker`.
+    pub fn borrow(ctx: Context<Borrow>, amount: u64) -> anchor_lang::Result<()> {
+        instructions::borrow::handler(ctx, amount)
+    }
+
     pub fn init_user_loan(ctx: Context<InitUserLoan>) -> anchor_lang::Result<()> {
         instructions::deposit_skr_and_borrow::init_user_loan(ctx)
     }
@@ -57,28 +87,180 @@ pub mod guardian_vault {
         instructions::harvest_repay::handler(ctx)
     }
 
+    pub fn repay(ctx: Context<Repay>, amount: u64) -> anchor_lang::Result<()> {
+        instructions::repay::handler(ctx, amount)
+    }
+
     pub fn withdraw_collateral(ctx: Context<WithdrawCollateral>) -> anchor_lang::Result<()> {
         instructions::withdraw_collateral::handler(ctx)
     }
 
-    pub fn liquidate_loan(ctx: Context<LiquidateLoan>) -> anchor_lang::Result<()> {
-        instructions::liquidate_loan::handler(ctx)
+    pub fn withdraw_partial_collateral(ctx: Context<WithdrawPartialCollateral>, amount: u64) -> anchor_lang::Result<()> {
+        instructions::withdraw_partial_collateral::handler(ctx, amount)
+    }
+
+    pub fn liquidate_loan(ctx: Context<LiquidateLoan>, repay_amount: u64) -> anchor_lang::Result<()> {
+        instructions::liquidate_loan::handler(ctx, repay_amount)
+    }
+
+    pub fn record_price(ctx: Context<RecordPrice>) -> anchor_lang::Result<()> {
+        instructions::price_history::record_price(ctx)
+    }
+
+    pub fn liquidate_underwater(ctx: Context<LiquidateUnderwater>) -> anchor_lang::Result<()> {
+        instructions::liquidate_underwater::handler(ctx)
+    }
+
+    pub fn collect_fees(ctx: Context<CollectFees>) -> anchor_lang::Result<()> {
+        instructions::fees::collect_fees(ctx)
+    }
+
+    pub fn fund_insurance(ctx: Context<FundInsurance>, amount: u64) -> anchor_lang::Result<()> {
+        instructions::bad_debt::fund_insurance(ctx, amount)
+    }
+
+    pub fn cover_bad_debt(ctx: Context<ManageBadDebt>, amount: u64) -> anchor_lang::Result<()> {
+        instructions::bad_debt::cover_bad_debt(ctx, amount)
+    }
+
+    pub fn socialize_bad_debt(ctx: Context<ManageBadDebt>, amount: u64) -> anchor_lang::Result<()> {
+        instructions::bad_debt::socialize_bad_debt(ctx, amount)
+    }
+
+    pub fn init_stability_pool(ctx: Context<InitStabilityPool>) -> anchor_lang::Result<()> {
+        instructions::stability_pool::init_stability_pool(ctx)
+    }
+
+    pub fn stability_deposit(ctx: Context<StabilityDepositAccounts>, amount: u64) -> anchor_lang::Result<()> {
+        instructions::stability_pool::deposit(ctx, amount)
+    }
+
+    pub fn stability_withdraw(ctx: Context<StabilityWithdrawAccounts>, amount: u64) -> anchor_lang::Result<()> {
+        instructions::stability_pool::withdraw(ctx, amount)
     }
 
-    pub fn emergency_pause(ctx: Context<AdminConfig>) -> anchor_lang::Result<()> {
-        instructions::admin::emergency_pause(ctx)
+    pub fn claim_stability_collateral(ctx: Context<StabilityWithdrawAccounts>) -> anchor_lang::Result<()> {
+        instructions::stability_pool::claim_collateral(ctx)
     }
 
-    pub fn unpause(ctx: Context<AdminConfig>) -> anchor_lang::Result<()> {
-        instructions::admin::unpause(ctx)
+    pub fn liquidate_with_pool(ctx: Context<LiquidateWithPool>) -> anchor_lang::Result<()> {
+        instructions::liquidate_with_pool::handler(ctx)
+    }
+
+    pub fn start_auction(ctx: Context<StartAuction>) -> anchor_lang::Result<()> {
+        instructions::auction::start_auction(ctx)
+    }
+
+    pub fn bid_auction(ctx: Context<BidAuction>, repay_amount: u64) -> anchor_lang::Result<()> {
+        instructions::auction::bid(ctx, repay_amount)
+    }
+
+    pub fn cancel_auction(ctx: Context<CancelAuction>) -> anchor_lang::Result<()> {
+        instructions::auction::cancel_auction(ctx)
+    }
+
+    pub fn trigger_settlement(ctx: Context<AdminConfig>, price: i64, expo: i32) -> anchor_lang::Result<()> {
+        instructions::settlement::trigger_settlement(ctx, price, expo)
+    }
+
+    pub fn settle_loan(ctx: Context<SettleLoan>) -> anchor_lang::Result<()> {
+        instructions::settlement::settle_loan(ctx)
+    }
+
+    pub fn redeem_jusdi(ctx: Context<RedeemJusdi>, amount: u64) -> anchor_lang::Result<()> {
+        instructions::settlement::redeem_jusdi(ctx, amount)
+    }
+
+    pub fn emergency_pause(ctx: Context<PauseConfig>, flags: u8) -> anchor_lang::Result<()> {
+        instructions::admin::emergency_pause(ctx, flags)
+    }
+
+    pub fn unpause(ctx: Context<AdminConfig>, flags: u8) -> anchor_lang::Result<()> {
+        instructions::admin::unpause(ctx, flags)
     }
 
     pub fn add_guardian(ctx: Context<AddGuardian>, guardian_pubkey: Pubkey, name: String, commission_bps: u16) -> anchor_lang::Result<()> {
         instructions::admin::add_guardian(ctx, guardian_pubkey, name, commission_bps)
     }
 
-    pub fn update_oracle(ctx: Context<AdminConfig>, new_price_feed: Pubkey) -> anchor_lang::Result<()> {
-        instructions::admin::update_oracle(ctx, new_price_feed)
+    pub fn set_role(ctx: Context<AdminConfig>, role: Role, key: Pubkey) -> anchor_lang::Result<()> {
+        instructions::admin::set_role(ctx, role, key)
+    }
+
+    pub fn revoke_role(ctx: Context<AdminConfig>, role: Role) -> anchor_lang::Result<()> {
+        instructions::admin::revoke_role(ctx, role)
+    }
+
+    pub fn update_oracle(ctx: Context<SetOracleFeed>) -> anchor_lang::Result<()> {
+        instructions::admin::update_oracle(ctx)
+    }
+
+    pub fn set_oracle_source(ctx: Context<SetOracleFeed>, index: u8, source: OracleSource) -> anchor_lang::Result<()> {
+        instructions::admin::set_oracle_source(ctx, index, source)
+    }
+
+    pub fn set_oracle_params(ctx: Context<OracleConfig>, max_oracle_staleness_secs: u64, max_confidence_bps: u16, min_oracle_sources: u8) -> anchor_lang::Result<()> {
+        instructions::admin::set_oracle_params(ctx, max_oracle_staleness_secs, max_confidence_bps, min_oracle_sources)
+    }
+
+    pub fn set_twap_window(ctx: Context<OracleConfig>, twap_window_secs: i64) -> anchor_lang::Result<()> {
+        instructions::admin::set_twap_window(ctx, twap_window_secs)
+    }
+
+    pub fn set_circuit_breaker(ctx: Context<OracleConfig>, max_price_deviation_bps: u16, price_deviation_window_secs: i64) -> anchor_lang::Result<()> {
+        instructions::admin::set_circuit_breaker(ctx, max_price_deviation_bps, price_deviation_window_secs)
+    }
+
+    pub fn reset_circuit_breaker(ctx: Context<AdminConfig>) -> anchor_lang::Result<()> {
+        instructions::admin::reset_circuit_breaker(ctx)
+    }
+
+    pub fn set_manual_price(ctx: Context<SetManualPrice>, price: i64, conf: u64, expo: i32) -> anchor_lang::Result<()> {
+        instructions::admin::set_manual_price(ctx, price, conf, expo)
+    }
+
+    pub fn propose_authority(ctx: Context<AdminConfig>, new_authority: Pubkey) -> anchor_lang::Result<()> {
+        instructions::admin::propose_authority(ctx, new_authority)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> anchor_lang::Result<()> {
+        instructions::admin::accept_authority(ctx)
+    }
+
+    pub fn update_risk_params(ctx: Context<RiskConfig>, params: RiskParams) -> anchor_lang::Result<()> {
+        instructions::admin::update_risk_params(ctx, params)
+    }
+
+    pub fn queue_change(ctx: Context<QueueChange>, change: ParamChange) -> anchor_lang::Result<()> {
+        instructions::timelock::queue_change(ctx, change)
+    }
+
+    pub fn execute_change(ctx: Context<ManagePendingChange>) -> anchor_lang::Result<()> {
+        instructions::timelock::execute_change(ctx)
+    }
+
+    pub fn cancel_change(ctx: Context<ManagePendingChange>) -> anchor_lang::Result<()> {
+        instructions::timelock::cancel_change(ctx)
+    }
+
+    pub fn update_close_factor(ctx: Context<RiskConfig>, close_factor_bps: u16) -> anchor_lang::Result<()> {
+        instructions::admin::update_close_factor(ctx, close_factor_bps)
+    }
+
+    pub fn set_liquidation_mode(ctx: Context<RiskConfig>, mode: u8, auction_max_discount_bps: u16, auction_duration_secs: i64) -> anchor_lang::Result<()> {
+        instructions::admin::set_liquidation_mode(ctx, mode, auction_max_discount_bps, auction_duration_secs)
+    }
+
+    pub fn set_yield_source(ctx: Context<SetYieldSource>, yield_source: u8) -> anchor_lang::Result<()> {
+        instructions::admin::set_yield_source(ctx, yield_source)
+    }
+
+    pub fn set_reward_rate(ctx: Context<RiskConfig>, reward_rate_bps_per_year: u16, max_harvest_reward: u64) -> anchor_lang::Result<()> {
+        instructions::admin::set_reward_rate(ctx, reward_rate_bps_per_year, max_harvest_reward)
+    }
+
+    pub fn set_stability_fee(ctx: Context<RiskConfig>, stability_fee_per_sec: u64) -> anchor_lang::Result<()> {
+        instructions::admin::set_stability_fee(ctx, stability_fee_per_sec)
     }
 
 
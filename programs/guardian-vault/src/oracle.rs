@@ -0,0 +1,681 @@
+use anchor_lang::prelude::*;
+use pyth_sdk_solana::state::SolanaPriceAccount;
+use crate::errors::VaultError;
+use crate::state::{VaultState, OracleSource, ManualPriceFeed, MAX_ORACLE_SOURCES, ORACLE_KIND_NONE, ORACLE_KIND_PYTH_PUSH, ORACLE_KIND_PYTH_PULL, ORACLE_KIND_SWITCHBOARD, ORACLE_KIND_MANUAL};
+
+/// jUSDi mint decimals; collateral values are in jUSDi base units.
+pub const JUSDI_DECIMALS: u8 = 6;
+
+/// A validated oracle reading: one whole token is worth `price * 10^expo` USD, +/- `conf`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    /// `price - conf`, for valuing collateral a borrower draws against.
+    pub fn lower_bound(&self) -> Result<OraclePrice> {
+        let price = self.price.checked_sub(self.conf as i64).ok_or(VaultError::MathOverflow)?;
+        require!(price > 0, VaultError::OracleConfidenceTooWide);
+        Ok(OraclePrice { price, conf: 0, ..*self })
+    }
+
+    /// `price` restated at 10^`expo`, rounded down.
+    pub fn price_at_expo(&self, expo: i32) -> Result<i64> {
+        let scale = 10i128.checked_pow(self.expo.abs_diff(expo)).ok_or(VaultError::MathOverflow)?;
+        let price = if self.expo >= expo {
+            (self.price as i128).checked_mul(scale).ok_or(VaultError::MathOverflow)?
+        } else {
+            (self.price as i128).checked_div(scale).ok_or(VaultError::MathOverflow)?
+        };
+        i64::try_from(price).map_err(|_| VaultError::MathOverflow.into())
+    }
+}
+
+/// Pyth oracle programs owning legacy push price accounts.
+pub const PYTH_PUSH_PROGRAM_IDS: [Pubkey; 2] = [
+    // FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH (mainnet)
+    Pubkey::new_from_array([
+        220, 229, 235, 225, 228, 156, 59, 159, 17, 76, 181, 84, 76, 80, 169, 158,
+        192, 214, 146, 214, 63, 86, 121, 90, 224, 41, 172, 131, 217, 234, 139, 226,
+    ]),
+    // gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s (devnet)
+    Pubkey::new_from_array([
+        10, 26, 152, 51, 163, 118, 85, 43, 86, 183, 202, 13, 237, 25, 41, 23,
+        0, 87, 232, 39, 160, 198, 39, 244, 182, 71, 185, 238, 144, 153, 175, 180,
+    ]),
+];
+/// Pyth receiver program (rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ); owns pull-oracle
+/// `PriceUpdateV2` accounts.
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    12, 183, 250, 187, 82, 247, 166, 72, 187, 91, 49, 125, 154, 1, 139, 144,
+    87, 203, 2, 71, 116, 250, 254, 1, 230, 196, 223, 152, 204, 56, 88, 129,
+]);
+/// Pyth push oracle program (pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT); owns the
+/// sponsored `PriceUpdateV2` feeds Pyth keeps updated.
+pub const PYTH_PUSH_ORACLE_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    12, 74, 160, 18, 142, 149, 211, 225, 98, 42, 165, 1, 197, 133, 169, 235,
+    7, 179, 115, 84, 193, 8, 234, 11, 121, 27, 69, 109, 199, 238, 163, 54,
+]);
+/// Switchboard on-demand programs; own `PullFeedAccountData` accounts.
+pub const SWITCHBOARD_PROGRAM_IDS: [Pubkey; 2] = [
+    // SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv (mainnet)
+    Pubkey::new_from_array([
+        6, 115, 189, 70, 242, 228, 126, 4, 241, 43, 217, 47, 183, 49, 150, 142,
+        205, 157, 151, 87, 194, 116, 218, 135, 71, 111, 70, 92, 4, 12, 101, 115,
+    ]),
+    // Aio4gaXjXzJNVLtzwtNVmSqGKpANtXhybbkhtAC94ji2 (devnet)
+    Pubkey::new_from_array([
+        144, 110, 20, 100, 197, 248, 183, 99, 60, 192, 90, 66, 76, 221, 179, 174,
+        205, 109, 171, 184, 174, 199, 71, 188, 79, 62, 17, 48, 30, 64, 99, 203,
+    ]),
+];
+
+/// Exponents accepted from a price source
+pub const MIN_PRICE_EXPO: i32 = -18;
+pub const MAX_PRICE_EXPO: i32 = 0;
+
+const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+const SWITCHBOARD_FEED_DISCRIMINATOR: [u8; 8] = [196, 27, 108, 196, 10, 215, 219, 40];
+
+// `PullFeedAccountData` field offsets, discriminator included
+const SWITCHBOARD_RESULT_VALUE_OFFSET: usize = 2264;
+const SWITCHBOARD_RESULT_STD_DEV_OFFSET: usize = 2280;
+const SWITCHBOARD_SUBMISSION_IDX_OFFSET: usize = 2361;
+const SWITCHBOARD_TIMESTAMPS_OFFSET: usize = 2952;
+/// Switchboard values carry 18 decimals; they're rescaled to 10^-8 to fit an i64.
+const SWITCHBOARD_PRECISION: u32 = 18;
+const SWITCHBOARD_EXPO: i32 = -8;
+
+/// Reads the SKR/USD price from the vault's oracle sources. Every configured source must
+/// be passed in, as `price_feed` or among `extra_feeds` (the instruction's remaining
+/// accounts), matched by address, so callers can't choose which sources are counted. A
+/// source that is stale, can't be parsed, or has a confidence interval wider than
+/// `max_confidence_bps` of the price is skipped. At least `min_oracle_sources` must
+/// remain, and the price is their [`median`].
+pub fn get_skr_price(
+    price_feed: &AccountInfo,
+    extra_feeds: &[AccountInfo],
+    vault_state: &VaultState,
+    current_timestamp: i64,
+) -> Result<OraclePrice> {
+    let mut fresh = Vec::with_capacity(MAX_ORACLE_SOURCES);
+    let mut first_error = None;
+
+    for source in vault_state.oracle_sources.iter().filter(|source| source.kind != ORACLE_KIND_NONE) {
+        let reading = if *price_feed.key == source.feed {
+            read_source(source, price_feed, vault_state, current_timestamp)
+        } else {
+            let account = extra_feeds.iter()
+                .find(|account| *account.key == source.feed)
+                .ok_or(VaultError::OracleSourceMissing)?;
+            read_source(source, account, vault_state, current_timestamp)
+        };
+        match reading {
+            Ok(price) => fresh.push(price),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+
+    // With too few sources, report why the first one was rejected
+    if fresh.len() < vault_state.min_oracle_sources.max(1) as usize {
+        return Err(first_error.unwrap_or_else(|| VaultError::NotEnoughOracleSources.into()));
+    }
+    median(&fresh).ok_or_else(|| VaultError::NotEnoughOracleSources.into())
+}
+
+/// Median of `prices`, which are in source order. With an even count the earlier source
+/// of the two middle readings wins, so two sources fall back to the first.
+pub fn median(prices: &[OraclePrice]) -> Option<OraclePrice> {
+    let mut order: Vec<usize> = (0..prices.len()).collect();
+    order.sort_by(|&a, &b| compare_prices(&prices[a], &prices[b]).then(a.cmp(&b)));
+
+    let mid = match prices.len() {
+        0 => return None,
+        len if len % 2 == 1 => order[len / 2],
+        len => order[len / 2 - 1].min(order[len / 2]),
+    };
+    Some(prices[mid])
+}
+
+/// Orders prices with different exponents by value.
+fn compare_prices(a: &OraclePrice, b: &OraclePrice) -> std::cmp::Ordering {
+    let expo = a.expo.min(b.expo);
+    let scaled = |p: &OraclePrice| {
+        10u128.checked_pow(p.expo.abs_diff(expo))
+            .and_then(|scale| (p.price as u128).checked_mul(scale))
+            .unwrap_or(u128::MAX)
+    };
+    scaled(a).cmp(&scaled(b))
+}
+
+/// Checks that `account` can serve as `source` right now: owned by the right program,
+/// parses, and passes the same checks as a price read.
+pub fn validate_source(source: &OracleSource, account: &AccountInfo, vault_state: &VaultState, current_timestamp: i64) -> Result<()> {
+    require_keys_eq!(*account.key, source.feed, VaultError::InvalidPriceFeed);
+    read_source(source, account, vault_state, current_timestamp).map(|_| ())
+}
+
+/// Reads one source and applies the vault's freshness and confidence checks.
+fn read_source(source: &OracleSource, account: &AccountInfo, vault_state: &VaultState, current_timestamp: i64) -> Result<OraclePrice> {
+    let price = match source.kind {
+        ORACLE_KIND_PYTH_PUSH => read_pyth_push(account)?,
+        ORACLE_KIND_PYTH_PULL => read_pyth_pull(account, &source.feed_id)?,
+        ORACLE_KIND_SWITCHBOARD => read_switchboard(account)?,
+        ORACLE_KIND_MANUAL => read_manual(account)?,
+        #[cfg(feature = "mock-oracle")]
+        crate::state::ORACLE_KIND_MOCK => read_mock(account)?,
+        _ => return Err(VaultError::InvalidPriceFeed.into()),
+    };
+
+    require!(price.price > 0, VaultError::InvalidPriceFeed);
+    require!((MIN_PRICE_EXPO..=MAX_PRICE_EXPO).contains(&price.expo), VaultError::InvalidPriceFeed);
+    require!(
+        current_timestamp.saturating_sub(price.publish_time) <= vault_state.max_oracle_staleness_secs as i64,
+        VaultError::StaleOracle
+    );
+
+    // conf / price <= max_confidence_bps / 10000
+    let max_conf = (price.price as u128)
+        .checked_mul(vault_state.max_confidence_bps as u128).ok_or(VaultError::MathOverflow)?
+        .checked_div(10000).ok_or(VaultError::MathOverflow)?;
+    require!(price.conf as u128 <= max_conf, VaultError::OracleConfidenceTooWide);
+
+    Ok(price)
+}
+
+/// Parses a legacy Pyth push price account.
+fn read_pyth_push(account: &AccountInfo) -> Result<OraclePrice> {
+    require!(PYTH_PUSH_PROGRAM_IDS.contains(account.owner), VaultError::InvalidPriceFeed);
+    let price_feed = SolanaPriceAccount::account_info_to_feed(account)
+        .map_err(|_| VaultError::InvalidPriceFeed)?;
+    let price_data = price_feed.get_price_unchecked();
+    Ok(OraclePrice {
+        price: price_data.price,
+        conf: price_data.conf,
+        expo: price_data.expo,
+        publish_time: price_data.publish_time,
+    })
+}
+
+/// Parses a Pyth `PriceUpdateV2` account. Only fully verified updates for `feed_id` are
+/// accepted.
+fn read_pyth_pull(account: &AccountInfo, feed_id: &[u8; 32]) -> Result<OraclePrice> {
+    require!(
+        *account.owner == PYTH_RECEIVER_PROGRAM_ID || *account.owner == PYTH_PUSH_ORACLE_PROGRAM_ID,
+        VaultError::InvalidPriceFeed
+    );
+    let data = account.try_borrow_data()?;
+    require!(data.get(..8) == Some(&PRICE_UPDATE_V2_DISCRIMINATOR[..]), VaultError::InvalidPriceFeed);
+
+    // discriminator, write_authority, then verification_level (1 = Full)
+    require!(data.get(40) == Some(&1), VaultError::InvalidPriceFeed);
+    let message = 41;
+    require!(data.get(message..message + 32) == Some(&feed_id[..]), VaultError::InvalidPriceFeed);
+
+    Ok(OraclePrice {
+        price: i64::from_le_bytes(read_bytes(&data, message + 32)?),
+        conf: u64::from_le_bytes(read_bytes(&data, message + 40)?),
+        expo: i32::from_le_bytes(read_bytes(&data, message + 48)?),
+        publish_time: i64::from_le_bytes(read_bytes(&data, message + 52)?),
+    })
+}
+
+/// Parses a Switchboard on-demand `PullFeedAccountData` account's current result.
+fn read_switchboard(account: &AccountInfo) -> Result<OraclePrice> {
+    require!(SWITCHBOARD_PROGRAM_IDS.contains(account.owner), VaultError::InvalidPriceFeed);
+    let data = account.try_borrow_data()?;
+    require!(data.get(..8) == Some(&SWITCHBOARD_FEED_DISCRIMINATOR[..]), VaultError::InvalidPriceFeed);
+
+    let scale = 10i128.pow(SWITCHBOARD_PRECISION - SWITCHBOARD_EXPO.unsigned_abs());
+    let value = i128::from_le_bytes(read_bytes(&data, SWITCHBOARD_RESULT_VALUE_OFFSET)?) / scale;
+    let std_dev = i128::from_le_bytes(read_bytes(&data, SWITCHBOARD_RESULT_STD_DEV_OFFSET)?) / scale;
+    let submission_idx = *data.get(SWITCHBOARD_SUBMISSION_IDX_OFFSET).ok_or(VaultError::InvalidPriceFeed)? as usize;
+    let publish_time = i64::from_le_bytes(read_bytes(&data, SWITCHBOARD_TIMESTAMPS_OFFSET + submission_idx * 8)?);
+
+    Ok(OraclePrice {
+        price: i64::try_from(value).map_err(|_| VaultError::InvalidPriceFeed)?,
+        conf: u64::try_from(std_dev).map_err(|_| VaultError::InvalidPriceFeed)?,
+        expo: SWITCHBOARD_EXPO,
+        publish_time,
+    })
+}
+
+/// Reads the program-owned [`ManualPriceFeed`].
+fn read_manual(account: &AccountInfo) -> Result<OraclePrice> {
+    let feed = Account::<ManualPriceFeed>::try_from(account).map_err(|_| VaultError::InvalidPriceFeed)?;
+    Ok(OraclePrice {
+        price: feed.price,
+        conf: feed.conf,
+        expo: feed.expo,
+        publish_time: feed.publish_time,
+    })
+}
+
+/// Reads a [`MockPriceFeed`](crate::state::MockPriceFeed).
+#[cfg(feature = "mock-oracle")]
+fn read_mock(account: &AccountInfo) -> Result<OraclePrice> {
+    let feed = Account::<crate::state::MockPriceFeed>::try_from(account).map_err(|_| VaultError::InvalidPriceFeed)?;
+    Ok(OraclePrice {
+        price: feed.price,
+        conf: feed.conf,
+        expo: feed.expo,
+        publish_time: feed.publish_time,
+    })
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| VaultError::InvalidPriceFeed.into())
+}
+
+/// Power of ten converting `amount * price` (token base units) into jUSDi base units.
+fn value_exponent(mint_decimals: u8, price: &OraclePrice) -> i32 {
+    price.expo + JUSDI_DECIMALS as i32 - mint_decimals as i32
+}
+
+/// USD value (jUSDi base units) of `amount` base units of a token with `mint_decimals`.
+pub fn collateral_value(amount: u64, mint_decimals: u8, price: &OraclePrice) -> Result<u128> {
+    let value = (amount as u128).checked_mul(price.price as u128).ok_or(VaultError::MathOverflow)?;
+    let exponent = value_exponent(mint_decimals, price);
+    let scale = 10u128.checked_pow(exponent.unsigned_abs()).ok_or(VaultError::MathOverflow)?;
+
+    let value = if exponent < 0 {
+        value.checked_div(scale).ok_or(VaultError::MathOverflow)?
+    } else {
+        value.checked_mul(scale).ok_or(VaultError::MathOverflow)?
+    };
+    Ok(value)
+}
+
+/// Token amount (base units) worth `value` jUSDi base units. Inverse of
+/// [`collateral_value`], rounded down.
+pub fn collateral_for_value(value: u128, mint_decimals: u8, price: &OraclePrice) -> Result<u64> {
+    let exponent = value_exponent(mint_decimals, price);
+    let scale = 10u128.checked_pow(exponent.unsigned_abs()).ok_or(VaultError::MathOverflow)?;
+
+    let amount = if exponent < 0 {
+        value.checked_mul(scale).ok_or(VaultError::MathOverflow)?
+            .checked_div(price.price as u128).ok_or(VaultError::MathOverflow)?
+    } else {
+        value.checked_div((price.price as u128).checked_mul(scale).ok_or(VaultError::MathOverflow)?)
+            .ok_or(VaultError::MathOverflow)?
+    };
+    u64::try_from(amount).map_err(|_| VaultError::MathOverflow.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_SKR: u64 = 1_000_000;
+
+    fn price(price: i64, expo: i32) -> OraclePrice {
+        OraclePrice { price, conf: 0, expo, publish_time: 0 }
+    }
+
+    #[test]
+    fn value_with_negative_expo() {
+        // $10.00 as 10_000_000 * 10^-6
+        let p = price(10_000_000, -6);
+        assert_eq!(collateral_value(ONE_SKR, 6, &p).unwrap(), 10_000_000);
+        assert_eq!(collateral_value(250 * ONE_SKR, 6, &p).unwrap(), 2_500_000_000);
+    }
+
+    #[test]
+    fn value_with_positive_expo() {
+        // $10 as 1 * 10^1
+        let p = price(1, 1);
+        assert_eq!(collateral_value(ONE_SKR, 6, &p).unwrap(), 10_000_000);
+    }
+
+    #[test]
+    fn value_with_zero_expo() {
+        let p = price(3, 0);
+        assert_eq!(collateral_value(2 * ONE_SKR, 6, &p).unwrap(), 6_000_000);
+    }
+
+    #[test]
+    fn value_uses_mint_decimals() {
+        let p = price(10_000_000, -6);
+        // 1 token with 9 decimals is still $10
+        assert_eq!(collateral_value(1_000_000_000, 9, &p).unwrap(), 10_000_000);
+        // 1 token with 0 decimals
+        assert_eq!(collateral_value(1, 0, &p).unwrap(), 10_000_000);
+        // Positive expo with more decimals than jUSDi
+        assert_eq!(collateral_value(1_000_000_000, 9, &price(1, 1)).unwrap(), 10_000_000);
+    }
+
+    #[test]
+    fn value_rounds_down() {
+        // 1 base unit at $0.50 is worth half a jUSDi base unit
+        let p = price(5, -1);
+        assert_eq!(collateral_value(1, 6, &p).unwrap(), 0);
+        assert_eq!(collateral_value(3, 6, &p).unwrap(), 1);
+    }
+
+    #[test]
+    fn value_overflow_errors() {
+        let p = price(i64::MAX, 30);
+        assert!(collateral_value(u64::MAX, 6, &p).is_err());
+    }
+
+    #[test]
+    fn collateral_for_value_inverts_value() {
+        for (p, decimals) in [(price(10_000_000, -6), 6), (price(1, 1), 6), (price(12_345, -3), 9), (price(7, 2), 0)] {
+            let value = collateral_value(123 * ONE_SKR, decimals, &p).unwrap();
+            let amount = collateral_for_value(value, decimals, &p).unwrap();
+            assert!(collateral_value(amount, decimals, &p).unwrap() <= value);
+            assert!(amount <= 123 * ONE_SKR);
+        }
+
+        // $500 at $10 is 50 SKR
+        assert_eq!(collateral_for_value(500_000_000, 6, &price(10_000_000, -6)).unwrap(), 50 * ONE_SKR);
+        assert_eq!(collateral_for_value(500_000_000, 6, &price(1, 1)).unwrap(), 50 * ONE_SKR);
+    }
+
+    #[test]
+    fn collateral_for_value_rounds_down() {
+        // $1 at $3 is 0.333333 SKR
+        assert_eq!(collateral_for_value(1_000_000, 6, &price(3, 0)).unwrap(), 333_333);
+    }
+
+    #[test]
+    fn price_at_expo_rescales() {
+        let p = price(10_000_000, -6);
+        assert_eq!(p.price_at_expo(-9).unwrap(), 10_000_000_000);
+        assert_eq!(p.price_at_expo(-1).unwrap(), 100);
+        assert_eq!(price(1_234_567, -6).price_at_expo(-2).unwrap(), 123);
+        assert!(price(i64::MAX, 0).price_at_expo(-9).is_err());
+    }
+
+    #[test]
+    fn lower_bound_subtracts_conf() {
+        let p = OraclePrice { price: 10_000_000, conf: 200_000, expo: -6, publish_time: 7 };
+        let lower = p.lower_bound().unwrap();
+        assert_eq!(lower.price, 9_800_000);
+        assert_eq!(lower.conf, 0);
+        assert_eq!(lower.expo, -6);
+        assert_eq!(lower.publish_time, 7);
+
+        let too_wide = OraclePrice { conf: 10_000_000, ..p };
+        assert!(too_wide.lower_bound().is_err());
+    }
+
+    const NOW: i64 = 1_700_000_000;
+    const FEED_ID: [u8; 32] = [7; 32];
+
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    impl TestAccount {
+        fn new(owner: Pubkey, data: Vec<u8>) -> Self {
+            TestAccount { key: Pubkey::new_unique(), owner, lamports: 1, data }
+        }
+
+        fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(&self.key, false, false, &mut self.lamports, &mut self.data, &self.owner, false, 0)
+        }
+    }
+
+    /// `PriceUpdateV2` with the given borsh-encoded verification level.
+    fn pyth_pull_account(price: i64, conf: u64, expo: i32, publish_time: i64, verification_level: &[u8]) -> TestAccount {
+        let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[0; 32]); // write_authority
+        data.extend_from_slice(verification_level);
+        data.extend_from_slice(&FEED_ID);
+        data.extend_from_slice(&price.to_le_bytes());
+        data.extend_from_slice(&conf.to_le_bytes());
+        data.extend_from_slice(&expo.to_le_bytes());
+        data.extend_from_slice(&publish_time.to_le_bytes());
+        data.extend_from_slice(&(publish_time - 1).to_le_bytes()); // prev_publish_time
+        data.extend_from_slice(&price.to_le_bytes()); // ema_price
+        data.extend_from_slice(&conf.to_le_bytes()); // ema_conf
+        data.extend_from_slice(&0u64.to_le_bytes()); // posted_slot
+        TestAccount::new(PYTH_RECEIVER_PROGRAM_ID, data)
+    }
+
+    /// `PullFeedAccountData` whose current result came from submission 3.
+    fn switchboard_account(value: i128, std_dev: i128, publish_time: i64) -> TestAccount {
+        let mut data = vec![0u8; 3208];
+        data[..8].copy_from_slice(&SWITCHBOARD_FEED_DISCRIMINATOR);
+        data[SWITCHBOARD_RESULT_VALUE_OFFSET..][..16].copy_from_slice(&value.to_le_bytes());
+        data[SWITCHBOARD_RESULT_STD_DEV_OFFSET..][..16].copy_from_slice(&std_dev.to_le_bytes());
+        data[SWITCHBOARD_SUBMISSION_IDX_OFFSET] = 3;
+        data[SWITCHBOARD_TIMESTAMPS_OFFSET + 3 * 8..][..8].copy_from_slice(&publish_time.to_le_bytes());
+        TestAccount::new(SWITCHBOARD_PROGRAM_IDS[0], data)
+    }
+
+    fn manual_account(price: i64, conf: u64, expo: i32, publish_time: i64) -> TestAccount {
+        let mut data = Vec::new();
+        ManualPriceFeed { price, conf, expo, publish_time, bump: 255 }.try_serialize(&mut data).unwrap();
+        TestAccount::new(crate::ID, data)
+    }
+
+    fn vault_with_sources(sources: &[(u8, Pubkey)], min_oracle_sources: u8) -> VaultState {
+        let mut vault_state = VaultState {
+            min_oracle_sources,
+            max_oracle_staleness_secs: 60,
+            max_confidence_bps: 200,
+            ..Default::default()
+        };
+        for (slot, (kind, feed)) in sources.iter().enumerate() {
+            vault_state.oracle_sources[slot] = OracleSource { kind: *kind, feed: *feed, feed_id: FEED_ID };
+        }
+        vault_state
+    }
+
+    #[test]
+    fn reads_fully_verified_pyth_pull_update() {
+        let mut account = pyth_pull_account(12_340_000, 1_000, -6, NOW, &[1]);
+        let p = read_pyth_pull(&account.info(), &FEED_ID).unwrap();
+        assert_eq!(p, OraclePrice { price: 12_340_000, conf: 1_000, expo: -6, publish_time: NOW });
+    }
+
+    #[test]
+    fn rejects_bad_pyth_pull_updates() {
+        // Partially verified (tag 0 + num_signatures)
+        let mut partial = pyth_pull_account(12_340_000, 1_000, -6, NOW, &[0, 5]);
+        assert!(read_pyth_pull(&partial.info(), &FEED_ID).is_err());
+
+        let mut account = pyth_pull_account(12_340_000, 1_000, -6, NOW, &[1]);
+        assert!(read_pyth_pull(&account.info(), &[8; 32]).is_err());
+
+        account.owner = Pubkey::new_unique();
+        assert!(read_pyth_pull(&account.info(), &FEED_ID).is_err());
+    }
+
+    #[test]
+    fn reads_switchboard_feed() {
+        // $12.50 +/- $0.01 at 18 decimals
+        let mut account = switchboard_account(12_500_000_000_000_000_000, 10_000_000_000_000_000, NOW - 5);
+        let p = read_switchboard(&account.info()).unwrap();
+        assert_eq!(p, OraclePrice { price: 1_250_000_000, conf: 1_000_000, expo: -8, publish_time: NOW - 5 });
+    }
+
+    #[test]
+    fn rejects_bad_switchboard_feeds() {
+        let mut negative = switchboard_account(-1_000_000_000_000_000_000, 0, NOW);
+        let vault_state = vault_with_sources(&[(ORACLE_KIND_SWITCHBOARD, negative.key)], 1);
+        let err = get_skr_price(&negative.info(), &[], &vault_state, NOW).unwrap_err();
+        assert_eq!(err, VaultError::InvalidPriceFeed.into());
+
+        let mut account = switchboard_account(12_500_000_000_000_000_000, 0, NOW);
+        account.data[0] ^= 1;
+        assert!(read_switchboard(&account.info()).is_err());
+
+        let mut account = switchboard_account(12_500_000_000_000_000_000, 0, NOW);
+        account.owner = Pubkey::new_unique();
+        assert!(read_switchboard(&account.info()).is_err());
+    }
+
+    #[test]
+    fn reads_manual_feed_owned_by_program() {
+        let mut account = manual_account(11, 0, 0, NOW);
+        assert_eq!(read_manual(&account.info()).unwrap(), OraclePrice { price: 11, conf: 0, expo: 0, publish_time: NOW });
+
+        account.owner = Pubkey::new_unique();
+        assert!(read_manual(&account.info()).is_err());
+    }
+
+    #[test]
+    fn median_picks_middle_value() {
+        let prices = [price(12, 0), price(1_000_000_000, -8), price(11_000, -3)];
+        assert_eq!(median(&prices), Some(price(11_000, -3)));
+        assert_eq!(median(&prices[..1]), Some(price(12, 0)));
+        assert_eq!(median(&[]), None);
+    }
+
+    #[test]
+    fn median_of_two_falls_back_to_first_source() {
+        assert_eq!(median(&[price(12, 0), price(10, 0)]), Some(price(12, 0)));
+        assert_eq!(median(&[price(10, 0), price(12, 0)]), Some(price(10, 0)));
+    }
+
+    #[test]
+    fn aggregates_fresh_sources() {
+        let mut pull = pyth_pull_account(12_000_000, 0, -6, NOW, &[1]);
+        let mut switchboard = switchboard_account(10_000_000_000_000_000_000, 0, NOW);
+        let mut manual = manual_account(11, 0, 0, NOW);
+        let vault_state = vault_with_sources(&[
+            (ORACLE_KIND_PYTH_PULL, pull.key),
+            (ORACLE_KIND_SWITCHBOARD, switchboard.key),
+            (ORACLE_KIND_MANUAL, manual.key),
+        ], 2);
+
+        let extra = [switchboard.info(), manual.info()];
+        let p = get_skr_price(&pull.info(), &extra, &vault_state, NOW).unwrap();
+        assert_eq!((p.price, p.expo), (11, 0));
+
+        // Sources can come in any order; unknown accounts are ignored
+        let mut stranger = manual_account(1, 0, 0, NOW);
+        let extra = [stranger.info(), pull.info(), switchboard.info()];
+        let p = get_skr_price(&manual.info(), &extra, &vault_state, NOW).unwrap();
+        assert_eq!((p.price, p.expo), (11, 0));
+    }
+
+    #[test]
+    fn skips_stale_and_uncertain_sources() {
+        let mut pull = pyth_pull_account(12_000_000, 0, -6, NOW - 61, &[1]);
+        let mut switchboard = switchboard_account(10_000_000_000_000_000_000, 1_000_000_000_000_000_000, NOW);
+        let mut manual = manual_account(11, 0, 0, NOW);
+        let vault_state = vault_with_sources(&[
+            (ORACLE_KIND_PYTH_PULL, pull.key),
+            (ORACLE_KIND_SWITCHBOARD, switchboard.key),
+            (ORACLE_KIND_MANUAL, manual.key),
+        ], 1);
+
+        let extra = [switchboard.info(), manual.info()];
+        let p = get_skr_price(&pull.info(), &extra, &vault_state, NOW).unwrap();
+        assert_eq!((p.price, p.expo), (11, 0));
+    }
+
+    #[test]
+    fn validates_candidate_sources() {
+        let mut pull = pyth_pull_account(12_000_000, 0, -6, NOW, &[1]);
+        let source = OracleSource { kind: ORACLE_KIND_PYTH_PULL, feed: pull.key, feed_id: FEED_ID };
+        let vault_state = vault_with_sources(&[], 1);
+        validate_source(&source, &pull.info(), &vault_state, NOW).unwrap();
+
+        // Wrong account, kind, staleness, exponent
+        let mut other = pyth_pull_account(12_000_000, 0, -6, NOW, &[1]);
+        assert!(validate_source(&source, &other.info(), &vault_state, NOW).is_err());
+        let switchboard = OracleSource { kind: ORACLE_KIND_SWITCHBOARD, ..source };
+        assert!(validate_source(&switchboard, &pull.info(), &vault_state, NOW).is_err());
+        assert_eq!(validate_source(&source, &pull.info(), &vault_state, NOW + 61).unwrap_err(), VaultError::StaleOracle.into());
+
+        let mut odd_expo = manual_account(12, 0, 3, NOW);
+        let manual = OracleSource { kind: ORACLE_KIND_MANUAL, feed: odd_expo.key, feed_id: [0; 32] };
+        assert!(validate_source(&manual, &odd_expo.info(), &vault_state, NOW).is_err());
+
+        // Sponsored feeds are owned by the push oracle program
+        pull.owner = PYTH_PUSH_ORACLE_PROGRAM_ID;
+        validate_source(&source, &pull.info(), &vault_state, NOW).unwrap();
+    }
+
+    fn mock_account(price: i64, expo: i32, publish_time: i64) -> TestAccount {
+        let mut data = Vec::new();
+        crate::state::MockPriceFeed { price, conf: 0, expo, publish_time, bump: 255 }.try_serialize(&mut data).unwrap();
+        TestAccount::new(crate::ID, data)
+    }
+
+    #[cfg(feature = "mock-oracle")]
+    #[test]
+    fn reads_mock_feed_through_oracle_path() {
+        let mut mock = mock_account(10_000_000, -6, NOW);
+        let vault_state = vault_with_sources(&[(crate::state::ORACLE_KIND_MOCK, mock.key)], 1);
+        let p = get_skr_price(&mock.info(), &[], &vault_state, NOW).unwrap();
+        assert_eq!((p.price, p.expo), (10_000_000, -6));
+
+        // Stale and crashed prices go through the same checks as real feeds
+        let err = get_skr_price(&mock.info(), &[], &vault_state, NOW + 61).unwrap_err();
+        assert_eq!(err, VaultError::StaleOracle.into());
+        let mut crashed = mock_account(0, -6, NOW);
+        let vault_state = vault_with_sources(&[(crate::state::ORACLE_KIND_MOCK, crashed.key)], 1);
+        assert!(get_skr_price(&crashed.info(), &[], &vault_state, NOW).is_err());
+    }
+
+    #[cfg(not(feature = "mock-oracle"))]
+    #[test]
+    fn rejects_mock_feed_without_feature() {
+        let mut mock = mock_account(10_000_000, -6, NOW);
+        let vault_state = vault_with_sources(&[(crate::state::ORACLE_KIND_MOCK, mock.key)], 1);
+        let err = get_skr_price(&mock.info(), &[], &vault_state, NOW).unwrap_err();
+        assert_eq!(err, VaultError::InvalidPriceFeed.into());
+    }
+
+    #[test]
+    fn requires_min_fresh_sources() {
+        let mut pull = pyth_pull_account(12_000_000, 0, -6, NOW - 61, &[1]);
+        let mut manual = manual_account(11, 0, 0, NOW);
+        let vault_state = vault_with_sources(&[
+            (ORACLE_KIND_PYTH_PULL, pull.key),
+            (ORACLE_KIND_MANUAL, manual.key),
+        ], 2);
+
+        // Reports why the first source was dropped
+        let extra = [manual.info()];
+        let err = get_skr_price(&pull.info(), &extra, &vault_state, NOW).unwrap_err();
+        assert_eq!(err, VaultError::StaleOracle.into());
+    }
+
+    #[test]
+    fn requires_every_configured_source() {
+        let mut pull = pyth_pull_account(12_000_000, 0, -6, NOW, &[1]);
+        let mut switchboard = switchboard_account(10_000_000_000_000_000_000, 0, NOW);
+        let mut manual = manual_account(30, 0, 0, NOW);
+        let vault_state = vault_with_sources(&[
+            (ORACLE_KIND_PYTH_PULL, pull.key),
+            (ORACLE_KIND_SWITCHBOARD, switchboard.key),
+            (ORACLE_KIND_MANUAL, manual.key),
+        ], 1);
+
+        // Leaving out a source that disagrees is rejected, even with enough sources left
+        let extra = [manual.info()];
+        let err = get_skr_price(&pull.info(), &extra, &vault_state, NOW).unwrap_err();
+        assert_eq!(err, VaultError::OracleSourceMissing.into());
+        let err = get_skr_price(&manual.info(), &[], &vault_state, NOW).unwrap_err();
+        assert_eq!(err, VaultError::OracleSourceMissing.into());
+
+        // Passing a stranger in a source's place doesn't count as that source
+        let mut stranger = manual_account(30, 0, 0, NOW);
+        let extra = [stranger.info(), manual.info()];
+        let err = get_skr_price(&pull.info(), &extra, &vault_state, NOW).unwrap_err();
+        assert_eq!(err, VaultError::OracleSourceMissing.into());
+
+        // With all three the outlier is outvoted
+        let extra = [switchboard.info(), manual.info()];
+        let p = get_skr_price(&pull.info(), &extra, &vault_state, NOW).unwrap();
+        assert_eq!((p.price, p.expo), (12_000_000, -6));
+    }
+}
@@ -0,0 +1,215 @@
+use anchor_lang::prelude::*;
+use crate::errors::VaultError;
+
+/// SPL stake pool program (SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy)
+pub const STAKE_POOL_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    6, 129, 78, 212, 202, 246, 138, 23, 70, 114, 253, 172, 134, 3, 26, 99,
+    232, 78, 161, 94, 250, 29, 68, 183, 34, 147, 246, 219, 219, 0, 22, 80,
+]);
+
+/// Fixed-point scale of exchange rates (lamports of stake per pool token)
+pub const EXCHANGE_RATE_SCALE: u128 = 1_000_000_000_000_000_000;
+
+// Borsh layout of `spl_stake_pool::state::StakePool`
+const ACCOUNT_TYPE_STAKE_POOL: u8 = 1;
+const POOL_MINT_OFFSET: usize = 162;
+const TOTAL_LAMPORTS_OFFSET: usize = 258;
+const POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+const LAST_UPDATE_EPOCH_OFFSET: usize = 274;
+
+/// The fields of a stake pool the vault prices collateral from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakePoolState {
+    pub pool_mint: Pubkey,
+    pub total_lamports: u64,
+    pub pool_token_supply: u64,
+    pub last_update_epoch: u64,
+}
+
+impl StakePoolState {
+    /// Lamports of stake backing one pool token, scaled by `EXCHANGE_RATE_SCALE`.
+    pub fn exchange_rate(&self) -> Result<u128> {
+        require!(self.pool_token_supply > 0, VaultError::InvalidStakePool);
+        (self.total_lamports as u128)
+            .checked_mul(EXCHANGE_RATE_SCALE).ok_or(VaultError::MathOverflow)?
+            .checked_div(self.pool_token_supply as u128)
+            .ok_or_else(|| VaultError::MathOverflow.into())
+    }
+}
+
+/// Parses an SPL stake pool account.
+pub fn load(account: &AccountInfo) -> Result<StakePoolState> {
+    require_keys_eq!(*account.owner, STAKE_POOL_PROGRAM_ID, VaultError::InvalidStakePool);
+    let data = account.try_borrow_data()?;
+    require!(data.first() == Some(&ACCOUNT_TYPE_STAKE_POOL), VaultError::InvalidStakePool);
+
+    Ok(StakePoolState {
+        pool_mint: Pubkey::new_from_array(read_bytes(&data, POOL_MINT_OFFSET)?),
+        total_lamports: u64::from_le_bytes(read_bytes(&data, TOTAL_LAMPORTS_OFFSET)?),
+        pool_token_supply: u64::from_le_bytes(read_bytes(&data, POOL_TOKEN_SUPPLY_OFFSET)?),
+        last_update_epoch: u64::from_le_bytes(read_bytes(&data, LAST_UPDATE_EPOCH_OFFSET)?),
+    })
+}
+
+/// Checks that `account` is a stake pool issuing the collateral mint.
+pub fn validate_pool(account: &AccountInfo, skr_mint: &Pubkey) -> Result<()> {
+    let pool = load(account)?;
+    require_keys_eq!(pool.pool_mint, *skr_mint, VaultError::InvalidStakePool);
+    Ok(())
+}
+
+/// Current exchange rate of the pool. The pool must have been updated this epoch, or
+/// its totals lag the epoch's staking rewards.
+pub fn current_exchange_rate(account: &AccountInfo, skr_mint: &Pubkey, epoch: u64) -> Result<u128> {
+    let pool = load(account)?;
+    require_keys_eq!(pool.pool_mint, *skr_mint, VaultError::InvalidStakePool);
+    require!(pool.last_update_epoch == epoch, VaultError::StakePoolNotUpdated);
+    pool.exchange_rate()
+}
+
+/// Pool tokens worth the gain in underlying value of `amount` tokens since the rate
+/// was `last_rate`, at the current `rate`. Zero if the rate has not risen.
+pub fn tokens_for_gain(amount: u64, last_rate: u128, rate: u128) -> Result<u64> {
+    if rate <= last_rate {
+        return Ok(0);
+    }
+    let gain = (amount as u128)
+        .checked_mul(rate - last_rate).ok_or(VaultError::MathOverflow)?
+        .checked_div(rate).ok_or(VaultError::MathOverflow)?;
+    u64::try_from(gain).map_err(|_| VaultError::MathOverflow.into())
+}
+
+/// Baseline rate once `added` tokens, worth `rate` each, join `amount` tokens whose baseline
+/// is `last_rate`. The gain is linear in the baseline, so weighting it by amount keeps what
+/// `amount` has already earned for the next harvest. An unset baseline starts at `rate`.
+pub fn blended_rate(amount: u64, last_rate: u128, added: u64, rate: u128) -> Result<u128> {
+    if last_rate == 0 || amount == 0 {
+        return Ok(rate);
+    }
+    let total = (amount as u128).checked_add(added as u128).ok_or(VaultError::MathOverflow)?;
+    (amount as u128)
+        .checked_mul(last_rate).ok_or(VaultError::MathOverflow)?
+        .checked_add((added as u128).checked_mul(rate).ok_or(VaultError::MathOverflow)?).ok_or(VaultError::MathOverflow)?
+        .checked_div(total)
+        .ok_or_else(|| VaultError::MathOverflow.into())
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| VaultError::InvalidStakePool.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    impl TestAccount {
+        fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(&self.key, false, false, &mut self.lamports, &mut self.data, &self.owner, false, 0)
+        }
+    }
+
+    /// Stand-in for an SPL stake pool account: zeroed except for the fields the vault reads.
+    fn stake_pool_account(pool_mint: Pubkey, total_lamports: u64, pool_token_supply: u64, last_update_epoch: u64) -> TestAccount {
+        let mut data = vec![0u8; 611];
+        data[0] = ACCOUNT_TYPE_STAKE_POOL;
+        data[POOL_MINT_OFFSET..][..32].copy_from_slice(pool_mint.as_ref());
+        data[TOTAL_LAMPORTS_OFFSET..][..8].copy_from_slice(&total_lamports.to_le_bytes());
+        data[POOL_TOKEN_SUPPLY_OFFSET..][..8].copy_from_slice(&pool_token_supply.to_le_bytes());
+        data[LAST_UPDATE_EPOCH_OFFSET..][..8].copy_from_slice(&last_update_epoch.to_le_bytes());
+        TestAccount { key: Pubkey::new_unique(), owner: STAKE_POOL_PROGRAM_ID, lamports: 1, data }
+    }
+
+    #[test]
+    fn reads_pool_fields() {
+        let mint = Pubkey::new_unique();
+        let mut account = stake_pool_account(mint, 1_100_000_000, 1_000_000_000, 600);
+        let pool = load(&account.info()).unwrap();
+        assert_eq!(pool, StakePoolState {
+            pool_mint: mint,
+            total_lamports: 1_100_000_000,
+            pool_token_supply: 1_000_000_000,
+            last_update_epoch: 600,
+        });
+        // 1.1 SOL per pool token
+        assert_eq!(current_exchange_rate(&account.info(), &mint, 600).unwrap(), 1_100_000_000_000_000_000);
+    }
+
+    #[test]
+    fn rejects_foreign_accounts() {
+        let mint = Pubkey::new_unique();
+
+        let mut wrong_owner = stake_pool_account(mint, 1, 1, 0);
+        wrong_owner.owner = Pubkey::new_unique();
+        assert!(validate_pool(&wrong_owner.info(), &mint).is_err());
+
+        let mut validator_list = stake_pool_account(mint, 1, 1, 0);
+        validator_list.data[0] = 2;
+        assert!(validate_pool(&validator_list.info(), &mint).is_err());
+
+        let mut truncated = stake_pool_account(mint, 1, 1, 0);
+        truncated.data.truncate(270);
+        assert!(validate_pool(&truncated.info(), &mint).is_err());
+
+        let mut other_mint = stake_pool_account(Pubkey::new_unique(), 1, 1, 0);
+        assert!(validate_pool(&other_mint.info(), &mint).is_err());
+
+        let mut ok = stake_pool_account(mint, 1, 1, 0);
+        assert!(validate_pool(&ok.info(), &mint).is_ok());
+    }
+
+    #[test]
+    fn requires_pool_updated_this_epoch() {
+        let mint = Pubkey::new_unique();
+        let mut account = stake_pool_account(mint, 1_000, 1_000, 599);
+        assert!(current_exchange_rate(&account.info(), &mint, 600).is_err());
+        assert!(current_exchange_rate(&account.info(), &mint, 599).is_ok());
+    }
+
+    #[test]
+    fn empty_pool_has_no_rate() {
+        let mint = Pubkey::new_unique();
+        let mut account = stake_pool_account(mint, 0, 0, 0);
+        assert!(current_exchange_rate(&account.info(), &mint, 0).is_err());
+    }
+
+    #[test]
+    fn gain_in_pool_tokens() {
+        let last = EXCHANGE_RATE_SCALE;
+        let rate = EXCHANGE_RATE_SCALE * 11 / 10;
+        // 110 tokens each up 0.1 SOL = 11 SOL, which is 10 tokens at 1.1 SOL
+        assert_eq!(tokens_for_gain(110_000_000, last, rate).unwrap(), 10_000_000);
+        // No gain when the rate is flat or has dropped
+        assert_eq!(tokens_for_gain(110_000_000, rate, rate).unwrap(), 0);
+        assert_eq!(tokens_for_gain(110_000_000, rate, last).unwrap(), 0);
+        // Rounds down
+        assert_eq!(tokens_for_gain(1, last, rate).unwrap(), 0);
+    }
+
+    #[test]
+    fn adding_tokens_keeps_unharvested_gain() {
+        let last = EXCHANGE_RATE_SCALE;
+        let rate = EXCHANGE_RATE_SCALE * 11 / 10;
+        let later = EXCHANGE_RATE_SCALE * 12 / 10;
+
+        // 110 tokens up 0.1 SOL each, then 110 more join at 1.1 SOL
+        let blended = blended_rate(110_000_000, last, 110_000_000, rate).unwrap();
+        assert_eq!(blended, EXCHANGE_RATE_SCALE * 105 / 100);
+        // The first 110 still harvest their 10 tokens of gain
+        assert_eq!(tokens_for_gain(220_000_000, blended, rate).unwrap(), 10_000_000);
+        // At 1.2 SOL: 22 SOL on the first 110 plus 11 SOL on the rest, at 1.2 SOL a token
+        assert_eq!(tokens_for_gain(220_000_000, blended, later).unwrap(), 27_500_000);
+
+        // No baseline yet
+        assert_eq!(blended_rate(0, 0, 110_000_000, rate).unwrap(), rate);
+        assert_eq!(blended_rate(110_000_000, 0, 110_000_000, rate).unwrap(), rate);
+    }
+}
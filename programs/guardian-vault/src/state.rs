@@ -1,35 +1,667 @@
 use anchor_lang::prelude::*;
+use crate::errors::VaultError;
 
+/// Default share of a loan's debt that can be repaid in one liquidation (50%)
+pub const DEFAULT_CLOSE_FACTOR_BPS: u16 = 5000;
+/// Loans with debt at or below this (1 jUSDi) can be liquidated in full regardless of close factor
+pub const LIQUIDATION_DUST_THRESHOLD: u64 = 1_000_000;
+/// Fixed-point scale for the borrow index and the per-second stability fee
+pub const INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+/// Max stability fee per second at INDEX_SCALE (~20% APR)
+pub const MAX_STABILITY_FEE_PER_SEC: u64 = 6_341_958_396;
+// Pause flags: each bit freezes one kind of operation
+pub const PAUSE_BORROW: u8 = 1 << 0;
+pub const PAUSE_DEPOSIT: u8 = 1 << 1;
+pub const PAUSE_REPAY: u8 = 1 << 2;
+pub const PAUSE_HARVEST: u8 = 1 << 3;
+pub const PAUSE_WITHDRAW: u8 = 1 << 4;
+pub const PAUSE_LIQUIDATE: u8 = 1 << 5;
+pub const PAUSE_ALL: u8 = PAUSE_BORROW | PAUSE_DEPOSIT | PAUSE_REPAY | PAUSE_HARVEST | PAUSE_WITHDRAW | PAUSE_LIQUIDATE;
+/// Default max age of an oracle price
+pub const DEFAULT_MAX_ORACLE_STALENESS_SECS: u64 = 60;
+/// Default max confidence interval as a share of price (2%, i.e. conf <= price / 50)
+pub const DEFAULT_MAX_CONFIDENCE_BPS: u16 = 200;
+/// Longest delay that can be put on queued admin changes (30 days)
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * 24 * 60 * 60;
+/// Oracle sources a vault can price from
+pub const MAX_ORACLE_SOURCES: usize = 3;
+// Oracle source kinds
+pub const ORACLE_KIND_NONE: u8 = 0;
+pub const ORACLE_KIND_PYTH_PUSH: u8 = 1;
+pub const ORACLE_KIND_PYTH_PULL: u8 = 2;
+pub const ORACLE_KIND_SWITCHBOARD: u8 = 3;
+pub const ORACLE_KIND_MANUAL: u8 = 4;
+/// Only readable in `mock-oracle` builds
+pub const ORACLE_KIND_MOCK: u8 = 5;
+/// Observations kept in the price history ring buffer
+pub const PRICE_HISTORY_CAPACITY: usize = 48;
+/// Recorded prices are USD per whole SKR at 10^PRICE_HISTORY_EXPO
+pub const PRICE_HISTORY_EXPO: i32 = -9;
+/// Default TWAP window (30 minutes)
+pub const DEFAULT_TWAP_WINDOW_SECS: i64 = 30 * 60;
+pub const MIN_TWAP_WINDOW_SECS: i64 = 5 * 60;
+pub const MAX_TWAP_WINDOW_SECS: i64 = 24 * 60 * 60;
+/// Default circuit breaker: a 20% move within 5 minutes halts borrowing
+pub const DEFAULT_MAX_PRICE_DEVIATION_BPS: u16 = 2000;
+pub const DEFAULT_PRICE_DEVIATION_WINDOW_SECS: i64 = 5 * 60;
+
+/// Rewards simulated at a fixed APY (devnet only)
+pub const YIELD_SOURCE_SIMULATED: u8 = 0;
+/// Rewards are the growth of the collateral stake pool's exchange rate
+pub const YIELD_SOURCE_STAKE_POOL: u8 = 1;
+
+/// Default simulated yield: 7% APY
+pub const DEFAULT_REWARD_RATE_BPS_PER_YEAR: u16 = 700;
+pub const MAX_REWARD_RATE_BPS_PER_YEAR: u16 = 10000;
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+// Fields are not append-only and there is no migration: layout changes need a fresh
+// deployment (see the README).
 #[account]
+#[derive(Default)]
 pub struct VaultState {
     pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub roles: RoleRegistry,
     pub labs_treasury: Pubkey,
     pub total_collateral: u64,
     pub total_debt: u64,
+    pub bad_debt: u64,
+    /// jUSDi in the insurance reserve; only `fund_insurance` adds to it, so tokens sent
+    /// straight to the vault's account don't count
+    pub insurance_balance: u64,
+    pub borrow_index: u128,
+    pub stability_fee_per_sec: u64,
+    pub last_accrual: i64,
+    pub accrued_fees: u64,
     pub harvest_fee_bps: u16,
+    pub yield_source: u8,
+    pub stake_pool: Pubkey,
+    /// APY of simulated yield
+    pub reward_rate_bps_per_year: u16,
+    /// Most SKR one simulated harvest can earn; 0 for no cap
+    pub max_harvest_reward: u64,
     pub base_ltv_bps: u16,
     pub skr_holder_bonus_bps: u16,
     pub cooldown_period: i64,
     pub liquidation_threshold_bps: u16,
     pub liquidation_penalty_bps: u16,
-    pub paused: bool,
-    pub skr_price_feed: Pubkey,
+    pub close_factor_bps: u16,
+    pub liquidation_mode: u8,
+    pub auction_max_discount_bps: u16,
+    pub auction_duration_secs: i64,
+    pub pause_flags: u8,
+    pub oracle_sources: [OracleSource; MAX_ORACLE_SOURCES],
+    pub min_oracle_sources: u8,
+    pub skr_mint: Pubkey,
+    pub skr_decimals: u8,
+    pub jusdi_mint: Pubkey,
+    pub max_oracle_staleness_secs: u64,
+    pub max_confidence_bps: u16,
+    pub twap_window_secs: i64,
+    /// Last price accepted by a borrow, at 10^PRICE_HISTORY_EXPO
+    pub last_price: i64,
+    pub last_price_at: i64,
+    pub max_price_deviation_bps: u16,
+    pub price_deviation_window_secs: i64,
+    pub breaker_tripped: bool,
+    pub timelock_delay: i64,
+    pub settlement_price: i64,
+    pub settlement_expo: i32,
+    pub settled_at: i64,
+    pub bump: u8,
+}
+
+impl VaultState {
+    /// Borrow LTV for a depositor: holders of 100+ SKR get the holder bonus.
+    pub fn ltv_for_balance(&self, skr_balance: u64) -> Result<u16> {
+        // 100 SKR = 100 * 10^6
+        let bonus_threshold = 100u64.checked_mul(10u64.pow(6)).ok_or(VaultError::MathOverflow)?;
+
+        if skr_balance >= bonus_threshold {
+            Ok(self.base_ltv_bps + self.skr_holder_bonus_bps)
+        } else {
+            Ok(self.base_ltv_bps)
+        }
+    }
+
+    /// Collateral seized per unit of repaid debt in fixed-penalty mode, in bps (1 + penalty).
+    pub fn penalty_multiplier_bps(&self) -> u128 {
+        10000 + self.liquidation_penalty_bps as u128
+    }
+
+    /// Grows the borrow index by the stability fee since the last accrual and books the
+    /// interest into `total_debt` and `accrued_fees`.
+    pub fn accrue_interest(&mut self, current_timestamp: i64) -> Result<()> {
+        let elapsed = current_timestamp.saturating_sub(self.last_accrual);
+        if elapsed <= 0 {
+            return Ok(());
+        }
+        self.last_accrual = current_timestamp;
+        if self.stability_fee_per_sec == 0 {
+            return Ok(());
+        }
+
+        let growth = (self.stability_fee_per_sec as u128)
+            .checked_mul(elapsed as u128).ok_or(VaultError::MathOverflow)?;
+        let new_index = self.borrow_index
+            .checked_mul(INDEX_SCALE.checked_add(growth).ok_or(VaultError::MathOverflow)?).ok_or(VaultError::MathOverflow)?
+            .checked_div(INDEX_SCALE).ok_or(VaultError::MathOverflow)?;
+
+        let new_total_debt = (self.total_debt as u128)
+            .checked_mul(new_index).ok_or(VaultError::MathOverflow)?
+            .checked_div(self.borrow_index).ok_or(VaultError::MathOverflow)?;
+        let new_total_debt = u64::try_from(new_total_debt).map_err(|_| VaultError::MathOverflow)?;
+        let interest = new_total_debt.checked_sub(self.total_debt).ok_or(VaultError::MathOverflow)?;
+
+        self.borrow_index = new_index;
+        self.total_debt = new_total_debt;
+        self.accrued_fees = self.accrued_fees.checked_add(interest).ok_or(VaultError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Accrues interest and hands back the fees booked since the last collection, which
+    /// the caller must mint. Until then `total_debt` exceeds the jUSDi that was minted.
+    pub fn take_accrued_fees(&mut self, current_timestamp: i64) -> Result<u64> {
+        self.accrue_interest(current_timestamp)?;
+        Ok(std::mem::take(&mut self.accrued_fees))
+    }
+
+    /// Lowers `total_debt`, saturating at zero. Per-loan debt rounds up against the index,
+    /// so the loans can add up to a few units more than the aggregate.
+    pub fn reduce_total_debt(&mut self, amount: u64) {
+        self.total_debt = self.total_debt.saturating_sub(amount);
+    }
+
+    /// Global settlement is terminal: once triggered the SKR price is frozen and no new debt can be created.
+    pub fn is_settled(&self) -> bool {
+        self.settled_at != 0
+    }
+
+    /// True if any of the operations in `flags` is paused.
+    pub fn is_paused(&self, flags: u8) -> bool {
+        self.pause_flags & flags != 0
+    }
+
+    /// The authority holds every role; other keys only the roles assigned to them.
+    pub fn has_role(&self, role: Role, key: &Pubkey) -> bool {
+        if *key == self.authority {
+            return true;
+        }
+        let holder = self.roles.get(role);
+        holder != Pubkey::default() && holder == *key
+    }
+
+    /// True if `feed` is one of the configured oracle sources.
+    pub fn has_oracle_source(&self, feed: &Pubkey) -> bool {
+        self.oracle_sources.iter().any(|source| source.kind != ORACLE_KIND_NONE && source.feed == *feed)
+    }
+
+    /// Minimum spacing between price history observations. A full ring buffer always
+    /// spans at least the TWAP window.
+    pub fn observation_interval(&self) -> i64 {
+        self.twap_window_secs / PRICE_HISTORY_CAPACITY as i64
+    }
+
+    /// Circuit breaker check for a borrow at `price` (10^PRICE_HISTORY_EXPO). If the price
+    /// moved more than `max_price_deviation_bps` from the last accepted price within the
+    /// deviation window, trips the breaker and returns the move in bps. Otherwise the price
+    /// becomes the last accepted price.
+    pub fn check_price_move(&mut self, price: i64, current_timestamp: i64) -> Result<Option<u64>> {
+        let recent = self.last_price_at != 0
+            && current_timestamp.saturating_sub(self.last_price_at) <= self.price_deviation_window_secs;
+        if recent && self.max_price_deviation_bps > 0 && self.last_price > 0 {
+            let deviation_bps = (price as i128 - self.last_price as i128).unsigned_abs()
+                .checked_mul(10000).ok_or(VaultError::MathOverflow)?
+                .checked_div(self.last_price as u128).ok_or(VaultError::MathOverflow)?;
+            if deviation_bps > self.max_price_deviation_bps as u128 {
+                self.breaker_tripped = true;
+                return Ok(Some(u64::try_from(deviation_bps).unwrap_or(u64::MAX)));
+            }
+        }
+
+        self.last_price = price;
+        self.last_price_at = current_timestamp;
+        Ok(None)
+    }
+
+    /// Simulated rewards on `collateral` over `time_elapsed` seconds at the configured
+    /// rate, limited to the per-harvest cap.
+    pub fn simulated_reward(&self, collateral: u64, time_elapsed: i64) -> Result<u64> {
+        if time_elapsed <= 0 {
+            return Ok(0);
+        }
+        // rewards = collateral * rate_bps * time / (10000 * seconds_per_year)
+        let rewards = (collateral as u128)
+            .checked_mul(self.reward_rate_bps_per_year as u128).ok_or(VaultError::MathOverflow)?
+            .checked_mul(time_elapsed as u128).ok_or(VaultError::MathOverflow)?
+            .checked_div(10000 * SECONDS_PER_YEAR as u128).ok_or(VaultError::MathOverflow)?;
+        let rewards = u64::try_from(rewards).map_err(|_| VaultError::MathOverflow)?;
+
+        if self.max_harvest_reward > 0 {
+            return Ok(rewards.min(self.max_harvest_reward));
+        }
+        Ok(rewards)
+    }
+
+    pub fn risk_params(&self) -> RiskParams {
+        RiskParams {
+            harvest_fee_bps: self.harvest_fee_bps,
+            base_ltv_bps: self.base_ltv_bps,
+            skr_holder_bonus_bps: self.skr_holder_bonus_bps,
+            cooldown_period: self.cooldown_period,
+            liquidation_threshold_bps: self.liquidation_threshold_bps,
+            liquidation_penalty_bps: self.liquidation_penalty_bps,
+        }
+    }
+
+    pub fn set_risk_params(&mut self, params: &RiskParams) {
+        self.harvest_fee_bps = params.harvest_fee_bps;
+        self.base_ltv_bps = params.base_ltv_bps;
+        self.skr_holder_bonus_bps = params.skr_holder_bonus_bps;
+        self.cooldown_period = params.cooldown_period;
+        self.liquidation_threshold_bps = params.liquidation_threshold_bps;
+        self.liquidation_penalty_bps = params.liquidation_penalty_bps;
+    }
+}
+
+/// A price source. Sources are tried in slot order; `feed_id` is only used by Pyth pull
+/// feeds, where it must match the id inside the `PriceUpdateV2` account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OracleSource {
+    pub kind: u8,
+    pub feed: Pubkey,
+    pub feed_id: [u8; 32],
+}
+
+impl OracleSource {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.kind <= ORACLE_KIND_MOCK, VaultError::InvalidOracleParams);
+        if self.kind != ORACLE_KIND_NONE {
+            require!(self.feed != Pubkey::default(), VaultError::InvalidOracleParams);
+        }
+        Ok(())
+    }
+}
+
+/// Price posted by the oracle admin, for use as an oracle source of last resort.
+#[account]
+pub struct ManualPriceFeed {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub bump: u8,
+}
+
+/// Test price feed with every field settable, so tests can drive price moves and
+/// staleness through the real oracle path. Only readable in `mock-oracle` builds.
+#[account]
+pub struct MockPriceFeed {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
     pub bump: u8,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Can only call `emergency_pause`
+    PauseGuardian,
+    /// Risk parameters, close factor, liquidation mode and stability fee
+    RiskAdmin,
+    OracleAdmin,
+    /// Insurance reserve and bad debt
+    TreasuryAdmin,
+}
+
+/// Per-role keys. `Pubkey::default()` means the role is unassigned.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RoleRegistry {
+    pub pause_guardian: Pubkey,
+    pub risk_admin: Pubkey,
+    pub oracle_admin: Pubkey,
+    pub treasury_admin: Pubkey,
+}
+
+impl RoleRegistry {
+    pub fn get(&self, role: Role) -> Pubkey {
+        match role {
+            Role::PauseGuardian => self.pause_guardian,
+            Role::RiskAdmin => self.risk_admin,
+            Role::OracleAdmin => self.oracle_admin,
+            Role::TreasuryAdmin => self.treasury_admin,
+        }
+    }
+
+    pub fn set(&mut self, role: Role, key: Pubkey) {
+        match role {
+            Role::PauseGuardian => self.pause_guardian = key,
+            Role::RiskAdmin => self.risk_admin = key,
+            Role::OracleAdmin => self.oracle_admin = key,
+            Role::TreasuryAdmin => self.treasury_admin = key,
+        }
+    }
+}
+
+/// Risk parameters settable at `initialize` and through `update_risk_params`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RiskParams {
+    pub harvest_fee_bps: u16,
+    pub base_ltv_bps: u16,
+    pub skr_holder_bonus_bps: u16,
+    pub cooldown_period: i64,
+    pub liquidation_threshold_bps: u16,
+    pub liquidation_penalty_bps: u16,
+}
+
+impl RiskParams {
+    // HIGH-01 FIX: Validate parameters
+    pub fn validate(&self) -> Result<()> {
+        let max_ltv_bps = self.base_ltv_bps as u32 + self.skr_holder_bonus_bps as u32;
+        require!(self.base_ltv_bps <= 8000, VaultError::LTVTooHigh); // Max 80% base LTV
+        require!(max_ltv_bps <= 9000, VaultError::LTVTooHigh); // Max 90% with bonus
+        require!(self.liquidation_threshold_bps as u32 > max_ltv_bps, VaultError::InvalidThreshold);
+        require!(self.liquidation_threshold_bps <= 9500, VaultError::InvalidThreshold); // Max 95%
+        require!(self.harvest_fee_bps <= 2000, VaultError::CommissionTooHigh); // Max 20% fee
+        require!(self.liquidation_penalty_bps <= 1500, VaultError::CommissionTooHigh); // Max 15% penalty
+        require!(self.cooldown_period >= 0, VaultError::GenericError); // Non-negative cooldown
+        Ok(())
+    }
+}
+
 #[account]
 pub struct UserLoan {
     pub owner: Pubkey,
     pub collateral_amount: u64,
     pub debt_amount: u64,
+    pub scaled_debt: u128,
     pub created_at: i64,
     pub last_harvest: i64,
+    /// Stake pool exchange rate at the last harvest; 0 until the first harvest after
+    /// collateral is added
+    pub last_exchange_rate: u128,
     pub unstake_requested_at: i64,
+    /// Start of the cooldown for `pending_withdrawal`, kept apart from the full-withdraw cooldown
+    pub partial_requested_at: i64,
+    pub pending_withdrawal: u64,
     pub guardian_pubkey: Pubkey,
     pub initial_ltv_bps: u16,
     pub bump: u8,
 }
 
+impl UserLoan {
+    /// Refreshes `debt_amount` (= scaled debt * index) at the vault's current borrow index.
+    pub fn sync_debt(&mut self, borrow_index: u128) -> Result<()> {
+        let debt = self.scaled_debt
+            .checked_mul(borrow_index).ok_or(VaultError::MathOverflow)?
+            .checked_add(INDEX_SCALE - 1).ok_or(VaultError::MathOverflow)?
+            .checked_div(INDEX_SCALE).ok_or(VaultError::MathOverflow)?;
+        self.debt_amount = u64::try_from(debt).map_err(|_| VaultError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn add_debt(&mut self, amount: u64, borrow_index: u128) -> Result<()> {
+        // Round up so new debt is never under-recorded
+        let scaled = (amount as u128)
+            .checked_mul(INDEX_SCALE).ok_or(VaultError::MathOverflow)?
+            .checked_add(borrow_index - 1).ok_or(VaultError::MathOverflow)?
+            .checked_div(borrow_index).ok_or(VaultError::MathOverflow)?;
+        self.scaled_debt = self.scaled_debt.checked_add(scaled).ok_or(VaultError::MathOverflow)?;
+        self.sync_debt(borrow_index)
+    }
+
+    pub fn reduce_debt(&mut self, amount: u64, borrow_index: u128) -> Result<()> {
+        if amount >= self.debt_amount {
+            self.scaled_debt = 0;
+            self.debt_amount = 0;
+            return Ok(());
+        }
+        let scaled = (amount as u128)
+            .checked_mul(INDEX_SCALE).ok_or(VaultError::MathOverflow)?
+            .checked_div(borrow_index).ok_or(VaultError::MathOverflow)?;
+        self.scaled_debt = self.scaled_debt.checked_sub(scaled).ok_or(VaultError::MathOverflow)?;
+        self.sync_debt(borrow_index)
+    }
+}
+
+/// Liquidations seize collateral at the oracle price plus a fixed penalty
+pub const LIQUIDATION_MODE_FIXED: u8 = 0;
+/// Liquidations go through a Dutch auction whose discount grows over time
+pub const LIQUIDATION_MODE_AUCTION: u8 = 1;
+
+/// Fixed-point scale for the stability pool's collateral-per-share accumulator
+pub const STABILITY_POOL_SCALE: u128 = 1_000_000_000_000_000_000;
+/// Shares per unit of jUSDi past which the pool starts a new epoch. By then the deposits
+/// left are dust next to the shares, and minting against them would blow up the share math.
+pub const STABILITY_POOL_MAX_SHARE_RATIO: u128 = 1_000_000_000;
+
+#[account]
+pub struct StabilityPool {
+    pub total_deposits: u64,
+    pub total_shares: u128,
+    pub collateral_per_share: u128,
+    /// Bumped whenever the pool is emptied; shares from earlier epochs hold no jUSDi
+    pub epoch: u64,
+    pub bump: u8,
+}
+
+impl StabilityPool {
+    /// jUSDi currently backing `shares`. Shrinks as the pool absorbs liquidations.
+    pub fn shares_to_deposit(&self, shares: u128) -> Result<u64> {
+        if self.total_shares == 0 {
+            return Ok(0);
+        }
+        let amount = shares
+            .checked_mul(self.total_deposits as u128).ok_or(VaultError::MathOverflow)?
+            .checked_div(self.total_shares).ok_or(VaultError::MathOverflow)?;
+        Ok(amount as u64)
+    }
+
+    /// Shares minted for a deposit of `amount` jUSDi at the current jUSDi-per-share rate.
+    pub fn deposit_to_shares(&self, amount: u64) -> Result<u128> {
+        if self.total_shares == 0 {
+            return Ok(amount as u128);
+        }
+        Ok((amount as u128)
+            .checked_mul(self.total_shares).ok_or(VaultError::MathOverflow)?
+            .checked_div(self.total_deposits as u128).ok_or(VaultError::MathOverflow)?)
+    }
+
+    /// SKR owed to `shares` under the accumulator, before subtracting what was already paid.
+    pub fn accrued_collateral(&self, shares: u128) -> Result<u128> {
+        accrued_collateral(shares, self.collateral_per_share)
+    }
+
+    /// Credits `collateral` SKR to all shares after the pool burns `debt` jUSDi. Once the
+    /// pool is empty, or the shares dwarf what is left, the epoch ends: the accumulator is
+    /// handed back for the epoch's snapshot and the pool starts over at one share per jUSDi.
+    pub fn absorb(&mut self, debt: u64, collateral: u64) -> Result<Option<u128>> {
+        require!(self.total_shares > 0, VaultError::StabilityPoolEmpty);
+
+        let per_share = (collateral as u128)
+            .checked_mul(STABILITY_POOL_SCALE).ok_or(VaultError::MathOverflow)?
+            .checked_div(self.total_shares).ok_or(VaultError::MathOverflow)?;
+        self.collateral_per_share = self.collateral_per_share.checked_add(per_share).ok_or(VaultError::MathOverflow)?;
+        self.total_deposits = self.total_deposits.checked_sub(debt).ok_or(VaultError::MathOverflow)?;
+
+        let max_shares = (self.total_deposits as u128)
+            .checked_mul(STABILITY_POOL_MAX_SHARE_RATIO).ok_or(VaultError::MathOverflow)?;
+        if self.total_shares <= max_shares {
+            return Ok(None);
+        }
+
+        // Any dust left over carries into the new epoch unowned
+        let final_collateral_per_share = self.collateral_per_share;
+        self.epoch = self.epoch.checked_add(1).ok_or(VaultError::MathOverflow)?;
+        self.total_shares = self.total_deposits as u128;
+        self.collateral_per_share = 0;
+        Ok(Some(final_collateral_per_share))
+    }
+}
+
+/// SKR owed to `shares` at accumulator value `collateral_per_share`.
+pub fn accrued_collateral(shares: u128, collateral_per_share: u128) -> Result<u128> {
+    Ok(shares
+        .checked_mul(collateral_per_share).ok_or(VaultError::MathOverflow)?
+        .checked_div(STABILITY_POOL_SCALE).ok_or(VaultError::MathOverflow)?)
+}
+
+#[account]
+pub struct StabilityDeposit {
+    pub owner: Pubkey,
+    pub shares: u128,
+    pub collateral_debt: u128,
+    /// Pool epoch the shares were minted in
+    pub epoch: u64,
+    pub bump: u8,
+}
+
+/// Final collateral-per-share of an ended stability pool epoch, so depositors from that
+/// epoch can still claim their SKR.
+#[account]
+pub struct StabilityEpoch {
+    pub epoch: u64,
+    pub collateral_per_share: u128,
+    pub bump: u8,
+}
+
+#[account]
+pub struct LiquidationAuction {
+    pub user_loan: Pubkey,
+    pub keeper: Pubkey,
+    pub started_at: i64,
+    pub bump: u8,
+}
+
+impl LiquidationAuction {
+    /// Current collateral discount: grows linearly from zero to `max_discount_bps` over `duration_secs`.
+    pub fn discount_bps(&self, vault_state: &VaultState, current_timestamp: i64) -> u16 {
+        let elapsed = current_timestamp.saturating_sub(self.started_at).max(0);
+        if elapsed >= vault_state.auction_duration_secs {
+            return vault_state.auction_max_discount_bps;
+        }
+        (vault_state.auction_max_discount_bps as i64 * elapsed / vault_state.auction_duration_secs) as u16
+    }
+}
+
+/// Ring buffer of SKR prices recorded by keepers through `record_price`.
+#[account]
+pub struct PriceHistory {
+    pub observations: [PriceObservation; PRICE_HISTORY_CAPACITY],
+    /// Slot the next observation is written to
+    pub head: u8,
+    pub count: u8,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PriceObservation {
+    pub price: i64,
+    pub timestamp: i64,
+}
+
+impl PriceHistory {
+    pub fn latest(&self) -> Option<PriceObservation> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(self.observations[(self.head as usize + PRICE_HISTORY_CAPACITY - 1) % PRICE_HISTORY_CAPACITY])
+    }
+
+    pub fn record(&mut self, observation: PriceObservation) {
+        self.observations[self.head as usize] = observation;
+        self.head = ((self.head as usize + 1) % PRICE_HISTORY_CAPACITY) as u8;
+        self.count = (self.count as usize + 1).min(PRICE_HISTORY_CAPACITY) as u8;
+    }
+
+    /// Time-weighted average price over `[now - window, now]`, each observation holding
+    /// until the next one. None unless the history covers the whole window and the latest
+    /// observation is at most `max_age` old.
+    pub fn twap(&self, now: i64, window: i64, max_age: i64) -> Option<i64> {
+        let latest = self.latest()?;
+        if window <= 0 || now.saturating_sub(latest.timestamp) > max_age {
+            return None;
+        }
+
+        let count = self.count as usize;
+        let oldest = (self.head as usize + PRICE_HISTORY_CAPACITY - count) % PRICE_HISTORY_CAPACITY;
+        let observation = |i: usize| self.observations[(oldest + i) % PRICE_HISTORY_CAPACITY];
+
+        let start = now.checked_sub(window)?;
+        if observation(0).timestamp > start {
+            return None;
+        }
+
+        let mut weighted: i128 = 0;
+        for i in 0..count {
+            let from = observation(i).timestamp.max(start);
+            let to = if i + 1 < count { observation(i + 1).timestamp } else { now };
+            if to > from {
+                weighted = weighted.checked_add((observation(i).price as i128).checked_mul((to - from) as i128)?)?;
+            }
+        }
+        i64::try_from(weighted / window as i128).ok()
+    }
+}
+
+/// An admin change waiting out the vault's timelock. One slot per change kind.
+#[account]
+pub struct PendingChange {
+    pub change: ParamChange,
+    pub queued_at: i64,
+    pub eta: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum ParamChange {
+    RiskParams(RiskParams),
+    Oracle(Pubkey),
+    TimelockDelay(i64),
+    OracleSource(u8, OracleSource),
+    /// max_oracle_staleness_secs, max_confidence_bps, min_oracle_sources
+    OracleParams(u64, u16, u8),
+    CloseFactor(u16),
+    /// mode, auction_max_discount_bps, auction_duration_secs
+    LiquidationMode(u8, u16, i64),
+    StabilityFee(u64),
+    /// yield_source, stake_pool
+    YieldSource(u8, Pubkey),
+    /// reward_rate_bps_per_year, max_harvest_reward
+    RewardRate(u16, u64),
+    /// max_price_deviation_bps, price_deviation_window_secs
+    CircuitBreaker(u16, i64),
+    TwapWindow(i64),
+    /// price, conf, expo
+    ManualPrice(i64, u64, i32),
+    /// price, expo
+    Settlement(i64, i32),
+}
+
+impl ParamChange {
+    /// Seed byte for the change's `PendingChange` slot.
+    pub fn kind(&self) -> u8 {
+        match self {
+            ParamChange::RiskParams(_) => 0,
+            ParamChange::Oracle(_) => 1,
+            ParamChange::TimelockDelay(_) => 2,
+            ParamChange::OracleSource(..) => 3,
+            ParamChange::OracleParams(..) => 4,
+            ParamChange::CloseFactor(_) => 5,
+            ParamChange::LiquidationMode(..) => 6,
+            ParamChange::StabilityFee(_) => 7,
+            ParamChange::YieldSource(..) => 8,
+            ParamChange::RewardRate(..) => 9,
+            ParamChange::CircuitBreaker(..) => 10,
+            ParamChange::TwapWindow(_) => 11,
+            ParamChange::ManualPrice(..) => 12,
+            ParamChange::Settlement(..) => 13,
+        }
+    }
+}
+
 #[account]
 pub struct GuardianList {
     pub guardians: Vec<GuardianInfo>,
@@ -43,3 +675,202 @@ pub struct GuardianInfo {
     pub name: String,
     pub commission_bps: u16,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(observations: &[(i64, i64)]) -> PriceHistory {
+        let mut history = PriceHistory {
+            observations: [PriceObservation::default(); PRICE_HISTORY_CAPACITY],
+            head: 0,
+            count: 0,
+            bump: 0,
+        };
+        for &(price, timestamp) in observations {
+            history.record(PriceObservation { price, timestamp });
+        }
+        history
+    }
+
+    #[test]
+    fn twap_weights_prices_by_time() {
+        // 10 for the first 75s of the window, 20 for the last 25s
+        let h = history(&[(10, 0), (20, 175)]);
+        assert_eq!(h.twap(200, 100, 60), Some(12));
+    }
+
+    #[test]
+    fn twap_ignores_short_wicks() {
+        let h = history(&[(100, 0), (10, 1_790), (100, 1_791)]);
+        assert_eq!(h.twap(1_800, 1_800, 60), Some(99));
+    }
+
+    #[test]
+    fn twap_requires_full_window() {
+        let h = history(&[(10, 150), (20, 175)]);
+        assert_eq!(h.twap(200, 100, 60), None);
+        assert_eq!(history(&[]).twap(200, 100, 60), None);
+    }
+
+    #[test]
+    fn twap_requires_recent_observation() {
+        let h = history(&[(10, 0), (20, 50)]);
+        assert_eq!(h.twap(200, 100, 60), None);
+        assert_eq!(h.twap(200, 100, 150), Some(20));
+    }
+
+    fn breaker(max_price_deviation_bps: u16) -> VaultState {
+        VaultState {
+            max_price_deviation_bps,
+            price_deviation_window_secs: 300,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn breaker_trips_on_large_move_within_window() {
+        let mut vault_state = breaker(2000);
+        assert_eq!(vault_state.check_price_move(1_000, 100).unwrap(), None);
+        assert_eq!(vault_state.check_price_move(1_200, 200).unwrap(), None);
+
+        // 1200 -> 900 is a 25% drop
+        assert_eq!(vault_state.check_price_move(900, 300).unwrap(), Some(2500));
+        assert!(vault_state.breaker_tripped);
+        // The rejected price doesn't become the reference
+        assert_eq!((vault_state.last_price, vault_state.last_price_at), (1_200, 200));
+    }
+
+    #[test]
+    fn breaker_ignores_old_reference_and_can_be_disabled() {
+        let mut vault_state = breaker(2000);
+        vault_state.check_price_move(1_000, 100).unwrap();
+        assert_eq!(vault_state.check_price_move(2_000, 401).unwrap(), None);
+        assert_eq!(vault_state.last_price, 2_000);
+
+        let mut vault_state = breaker(0);
+        vault_state.check_price_move(1_000, 100).unwrap();
+        assert_eq!(vault_state.check_price_move(10, 101).unwrap(), None);
+        assert!(!vault_state.breaker_tripped);
+    }
+
+    fn stability_pool() -> StabilityPool {
+        StabilityPool { total_deposits: 0, total_shares: 0, collateral_per_share: 0, epoch: 0, bump: 0 }
+    }
+
+    fn stability_deposit(pool: &mut StabilityPool, amount: u64) -> u128 {
+        let shares = pool.deposit_to_shares(amount).unwrap();
+        pool.total_shares += shares;
+        pool.total_deposits += amount;
+        shares
+    }
+
+    #[test]
+    fn stability_pool_survives_repeated_near_total_absorption() {
+        let mut pool = stability_pool();
+        let mut epochs_ended = 0;
+        for _ in 0..100 {
+            // A fresh 1000 jUSDi deposit still gets shares worth what was put in
+            let shares = stability_deposit(&mut pool, 1_000_000_000);
+            assert!(shares > 0);
+            assert!(pool.shares_to_deposit(shares).unwrap() >= 999_999_999);
+
+            // Liquidations burn 99.9% of the pool
+            let debt = pool.total_deposits - pool.total_deposits / 1000;
+            let per_share_before = pool.collateral_per_share;
+            if pool.absorb(debt, 1_000_000_000).unwrap().is_some() {
+                epochs_ended += 1;
+            } else {
+                // The newest deposit holds nearly all shares and gets nearly all the SKR
+                let gain = accrued_collateral(shares, pool.collateral_per_share - per_share_before).unwrap();
+                assert!(gain >= 998_000_000, "gain {}", gain);
+            }
+            assert!(pool.total_shares <= (pool.total_deposits as u128).max(1) * STABILITY_POOL_MAX_SHARE_RATIO);
+        }
+        assert_eq!(pool.epoch, epochs_ended);
+        assert!(epochs_ended >= 25);
+    }
+
+    #[test]
+    fn emptied_stability_pool_starts_new_epoch() {
+        let mut pool = stability_pool();
+        let a = stability_deposit(&mut pool, 1_000_000_000);
+        let b = stability_deposit(&mut pool, 3_000_000_000);
+
+        let final_per_share = pool.absorb(4_000_000_000, 2_000_000_000).unwrap().unwrap();
+        assert_eq!(pool.epoch, 1);
+        assert_eq!((pool.total_deposits, pool.total_shares, pool.collateral_per_share), (0, 0, 0));
+
+        // The old depositors claim their SKR against the epoch snapshot
+        assert_eq!(accrued_collateral(a, final_per_share).unwrap(), 500_000_000);
+        assert_eq!(accrued_collateral(b, final_per_share).unwrap(), 1_500_000_000);
+
+        // New deposits mint one share per jUSDi again
+        assert_eq!(stability_deposit(&mut pool, 7), 7);
+    }
+
+    #[test]
+    fn collected_fees_reconcile_supply_with_debt() {
+        let mut vault_state = VaultState {
+            total_debt: 1_000_000_000,
+            borrow_index: INDEX_SCALE,
+            // ~10% a year
+            stability_fee_per_sec: 3_170_979_198,
+            ..Default::default()
+        };
+        // jUSDi minted by borrows
+        let mut supply = vault_state.total_debt;
+
+        vault_state.accrue_interest(SECONDS_PER_YEAR as i64 / 2).unwrap();
+        assert!(vault_state.total_debt > supply);
+        assert_eq!(vault_state.total_debt, supply + vault_state.accrued_fees);
+
+        supply += vault_state.take_accrued_fees(SECONDS_PER_YEAR as i64).unwrap();
+        assert_eq!(vault_state.accrued_fees, 0);
+        assert_eq!(supply, vault_state.total_debt);
+        assert!(supply > 1_090_000_000);
+
+        // A repay burns what it retires, so the two stay in step
+        supply -= 400_000_000;
+        vault_state.reduce_total_debt(400_000_000);
+        assert_eq!(vault_state.take_accrued_fees(SECONDS_PER_YEAR as i64).unwrap(), 0);
+        assert_eq!(supply, vault_state.total_debt);
+    }
+
+    #[test]
+    fn simulated_reward_follows_rate_and_cap() {
+        let mut vault_state = VaultState {
+            reward_rate_bps_per_year: DEFAULT_REWARD_RATE_BPS_PER_YEAR,
+            ..Default::default()
+        };
+        // 7% of 1000 SKR over a year, and over half a year
+        assert_eq!(vault_state.simulated_reward(1_000_000_000, SECONDS_PER_YEAR as i64).unwrap(), 70_000_000);
+        assert_eq!(vault_state.simulated_reward(1_000_000_000, SECONDS_PER_YEAR as i64 / 2).unwrap(), 35_000_000);
+        assert_eq!(vault_state.simulated_reward(1_000_000_000, 0).unwrap(), 0);
+
+        vault_state.reward_rate_bps_per_year = 1500;
+        assert_eq!(vault_state.simulated_reward(1_000_000_000, SECONDS_PER_YEAR as i64).unwrap(), 150_000_000);
+
+        vault_state.max_harvest_reward = 100_000_000;
+        assert_eq!(vault_state.simulated_reward(1_000_000_000, SECONDS_PER_YEAR as i64).unwrap(), 100_000_000);
+        assert_eq!(vault_state.simulated_reward(1_000_000_000, SECONDS_PER_YEAR as i64 / 10).unwrap(), 15_000_000);
+
+        vault_state.reward_rate_bps_per_year = 0;
+        assert_eq!(vault_state.simulated_reward(1_000_000_000, SECONDS_PER_YEAR as i64).unwrap(), 0);
+    }
+
+    #[test]
+    fn ring_buffer_wraps() {
+        let mut h = history(&[]);
+        for i in 0..(PRICE_HISTORY_CAPACITY as i64 + 5) {
+            h.record(PriceObservation { price: i, timestamp: i * 10 });
+        }
+        assert_eq!(h.count as usize, PRICE_HISTORY_CAPACITY);
+        assert_eq!(h.head, 5);
+        assert_eq!(h.latest(), Some(PriceObservation { price: 52, timestamp: 520 }));
+
+        // Oldest kept observation is #5 at t=50
+        assert_eq!(h.twap(520, 470, 0), Some((5..52).sum::<i64>() * 10 / 470));
+        assert_eq!(h.twap(520, 471, 0), None);
+    }
+}
@@ -106,10 +106,11 @@ async function main() {
         console.log(`   New Feed: ${priceFeedPubkey.toBase58()}`);
 
         const tx = await program.methods
-            .updateOracle(priceFeedPubkey)
+            .updateOracle()
             .accounts({
-                authority: walletKeypair.publicKey,
+                admin: walletKeypair.publicKey,
                 vaultState: vaultStatePda,
+                newPriceFeed: priceFeedPubkey,
             })
             .signers([walletKeypair])
             .rpc();
@@ -15,6 +15,26 @@ describe("guardian-vault", () => {
     let userSkrAccount;
     const treasury = Keypair.generate();
 
+    // Posts a price to mock feed 0 (needs a mock-oracle build), stamped with the cluster time by default
+    const setMockPrice = async (price, expo = -6, publishTime = null) => {
+        const [mockPriceFeedPda] = await PublicKey.findProgramAddress(
+            [Buffer.from("mock_price"), Buffer.from([0])],
+            program.programId
+        );
+        if (publishTime === null) {
+            publishTime = await provider.connection.getBlockTime(await provider.connection.getSlot());
+        }
+        await program.methods.setMockPrice(0, new anchor.BN(price), new anchor.BN(0), expo, new anchor.BN(publishTime))
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+                mockPriceFeed: mockPriceFeedPda,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        return mockPriceFeedPda;
+    };
+
     it("Is initialized!", async () => {
         [vaultStatePda, vaultBump] = await PublicKey.findProgramAddress(
             [Buffer.from("vault_state")],
@@ -55,12 +75,15 @@ describe("guardian-vault", () => {
                 authority: provider.wallet.publicKey,
                 vaultState: vaultStatePda,
                 labsTreasury: treasury.publicKey,
+                skrMint: mockSkrMint,
+                jusdiMint: jusdiMint,
                 systemProgram: SystemProgram.programId,
             })
             .rpc();
 
         const state = await program.account.vaultState.fetch(vaultStatePda);
         assert.equal(state.harvestFeeBps, 1000);
+        assert.ok(state.jusdiMint.equals(jusdiMint));
     });
 
     it("Mints Mock SKR", async () => {
@@ -135,14 +158,15 @@ describe("guardian-vault", () => {
         // LTV = 55% -> Max Borrow $550.
         // 500 < 550, should succeed.
 
-        // Initialize Oracle Price Feed
-        const priceFeed = Keypair.generate();
-        console.log("Price Feed:", priceFeed.publicKey.toBase58());
+        // Price SKR at $10.00 from a mock feed
+        const priceFeed = await setMockPrice(10000000);
+        console.log("Price Feed:", priceFeed.toBase58());
 
-        await program.methods.updateOracle(priceFeed.publicKey)
+        await program.methods.setOracleSource(0, { kind: 5, feed: priceFeed, feedId: new Array(32).fill(0) })
             .accounts({
                 admin: provider.wallet.publicKey,
                 vaultState: vaultStatePda,
+                newPriceFeed: priceFeed,
             })
             .rpc();
 
@@ -163,7 +187,9 @@ describe("guardian-vault", () => {
                 userJusdiAccount: userJusdiAccount,
                 jusdiMint: jusdiMint,
                 mockSkrMint: mockSkrMint,
-                skrPriceFeed: priceFeed.publicKey,
+                skrPriceFeed: priceFeed,
+                // Only read in stake-pool yield mode
+                stakePool: PublicKey.default,
                 tokenProgram: TOKEN_PROGRAM_ID,
                 systemProgram: SystemProgram.programId,
                 associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
@@ -174,4 +200,569 @@ describe("guardian-vault", () => {
         console.log("Loan Debt:", loan.debtAmount.toString());
         assert.equal(loan.debtAmount.toString(), "550000000");
     });
+
+    it("Repays part of the debt", async () => {
+        const [userLoanPda] = await PublicKey.findProgramAddress(
+            [Buffer.from("user_loan"), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+
+        const userJusdiAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            provider.wallet.payer,
+            jusdiMint,
+            provider.wallet.publicKey
+        )).address;
+
+        // Repay 50 jUSDi of the 550 borrowed
+        await program.methods.repay(new anchor.BN(50000000))
+            .accounts({
+                user: provider.wallet.publicKey,
+                userLoan: userLoanPda,
+                vaultState: vaultStatePda,
+                userJusdiAccount: userJusdiAccount,
+                jusdiMint: jusdiMint,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc();
+
+        const loan = await program.account.userLoan.fetch(userLoanPda);
+        assert.equal(loan.debtAmount.toString(), "500000000");
+
+        const state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.equal(state.totalDebt.toString(), "500000000");
+    });
+
+    it("Adds collateral and borrows a chosen amount", async () => {
+        const [userLoanPda] = await PublicKey.findProgramAddress(
+            [Buffer.from("user_loan"), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const state = await program.account.vaultState.fetch(vaultStatePda);
+
+        const vaultSkrAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            provider.wallet.payer,
+            mockSkrMint,
+            vaultStatePda,
+            true
+        )).address;
+
+        const userJusdiAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            provider.wallet.payer,
+            jusdiMint,
+            provider.wallet.publicKey
+        )).address;
+
+        // Deposit another 100 SKR without borrowing
+        await program.methods.addCollateral(new anchor.BN(100000000))
+            .accounts({
+                user: provider.wallet.publicKey,
+                userLoan: userLoanPda,
+                vaultState: vaultStatePda,
+                vaultSkrAccount: vaultSkrAccount,
+                userSkrAccount: userSkrAccount,
+                mockSkrMint: mockSkrMint,
+                stakePool: PublicKey.default,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc();
+
+        let loan = await program.account.userLoan.fetch(userLoanPda);
+        assert.equal(loan.collateralAmount.toString(), "200000000");
+        assert.equal(loan.debtAmount.toString(), "500000000");
+
+        await setMockPrice(10000000);
+
+        // Borrow 100 jUSDi: 600 / 2000 = 30% LTV, under the 55% cap
+        await program.methods.borrow(new anchor.BN(100000000))
+            .accounts({
+                user: provider.wallet.publicKey,
+                userLoan: userLoanPda,
+                vaultState: vaultStatePda,
+                userJusdiAccount: userJusdiAccount,
+                userSkrAccount: userSkrAccount,
+                jusdiMint: jusdiMint,
+                skrPriceFeed: state.oracleSources[0].feed,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc();
+
+        loan = await program.account.userLoan.fetch(userLoanPda);
+        assert.equal(loan.debtAmount.toString(), "600000000");
+
+        // 600 + 600 = 1200 > 1100 cap
+        try {
+            await program.methods.borrow(new anchor.BN(600000000))
+                .accounts({
+                    user: provider.wallet.publicKey,
+                    userLoan: userLoanPda,
+                    vaultState: vaultStatePda,
+                    userJusdiAccount: userJusdiAccount,
+                    userSkrAccount: userSkrAccount,
+                    jusdiMint: jusdiMint,
+                    skrPriceFeed: state.oracleSources[0].feed,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .rpc();
+            assert.fail("Borrow above the LTV cap should fail");
+        } catch (err) {
+            assert.include(err.toString(), "BorrowExceedsLtv");
+        }
+    });
+
+    it("Updates risk parameters", async () => {
+        const params = {
+            harvestFeeBps: 500,            // 5% Fee
+            baseLtvBps: 5000,
+            skrHolderBonusBps: 500,
+            cooldownPeriod: new anchor.BN(2),
+            liquidationThresholdBps: 8000,
+            liquidationPenaltyBps: 500,
+        };
+
+        await program.methods.updateRiskParams(params)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        const state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.equal(state.harvestFeeBps, 500);
+
+        // Threshold must stay above the max borrow LTV (55%)
+        try {
+            await program.methods.updateRiskParams({ ...params, liquidationThresholdBps: 5000 })
+                .accounts({
+                    admin: provider.wallet.publicKey,
+                    vaultState: vaultStatePda,
+                })
+                .rpc();
+            assert.fail("Threshold below LTV should fail");
+        } catch (err) {
+            assert.include(err.toString(), "InvalidThreshold");
+        }
+
+        // A lowered LTV applies to existing loans: 600 debt on $2000 is over 20% + 5%
+        await program.methods.updateRiskParams({ ...params, baseLtvBps: 2000 })
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        const [userLoanPda] = await PublicKey.findProgramAddress(
+            [Buffer.from("user_loan"), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const userJusdiAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            provider.wallet.payer,
+            jusdiMint,
+            provider.wallet.publicKey
+        )).address;
+        await setMockPrice(10000000);
+        try {
+            await program.methods.borrow(new anchor.BN(1000000))
+                .accounts({
+                    user: provider.wallet.publicKey,
+                    userLoan: userLoanPda,
+                    vaultState: vaultStatePda,
+                    userJusdiAccount: userJusdiAccount,
+                    userSkrAccount: userSkrAccount,
+                    jusdiMint: jusdiMint,
+                    skrPriceFeed: state.oracleSources[0].feed,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .rpc();
+            assert.fail("Borrow above the lowered LTV should fail");
+        } catch (err) {
+            assert.include(err.toString(), "BorrowExceedsLtv");
+        }
+
+        await program.methods.updateRiskParams(params)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+    });
+
+    it("Transfers authority in two steps", async () => {
+        const newAuthority = Keypair.generate();
+
+        await program.methods.proposeAuthority(newAuthority.publicKey)
+            .accounts({
+                authority: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        // Only the proposed key can accept
+        try {
+            await program.methods.acceptAuthority()
+                .accounts({
+                    pendingAuthority: provider.wallet.publicKey,
+                    vaultState: vaultStatePda,
+                })
+                .rpc();
+            assert.fail("Accept from the wrong key should fail");
+        } catch (err) {
+            assert.include(err.toString(), "Unauthorized");
+        }
+
+        await program.methods.acceptAuthority()
+            .accounts({
+                pendingAuthority: newAuthority.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .signers([newAuthority])
+            .rpc();
+
+        let state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.ok(state.authority.equals(newAuthority.publicKey));
+
+        // Hand it back for the remaining tests
+        await program.methods.proposeAuthority(provider.wallet.publicKey)
+            .accounts({
+                authority: newAuthority.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .signers([newAuthority])
+            .rpc();
+        await program.methods.acceptAuthority()
+            .accounts({
+                pendingAuthority: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.ok(state.authority.equals(provider.wallet.publicKey));
+    });
+
+    it("Lets the pause guardian pause but not unpause", async () => {
+        const guardian = Keypair.generate();
+
+        await program.methods.setRole({ pauseGuardian: {} }, guardian.publicKey)
+            .accounts({
+                authority: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        // Freeze new borrows only (PAUSE_BORROW)
+        await program.methods.emergencyPause(1)
+            .accounts({
+                admin: guardian.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .signers([guardian])
+            .rpc();
+
+        let state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.equal(state.pauseFlags, 1);
+
+        // Unpause stays with the authority
+        try {
+            await program.methods.unpause(1)
+                .accounts({
+                    authority: guardian.publicKey,
+                    vaultState: vaultStatePda,
+                })
+                .signers([guardian])
+                .rpc();
+            assert.fail("Pause guardian should not be able to unpause");
+        } catch (err) {
+            assert.include(err.toString(), "ConstraintHasOne");
+        }
+
+        await program.methods.unpause(1)
+            .accounts({
+                authority: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        await program.methods.revokeRole({ pauseGuardian: {} })
+            .accounts({
+                authority: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.equal(state.pauseFlags, 0);
+        assert.ok(state.roles.pauseGuardian.equals(PublicKey.default));
+    });
+
+    it("Adds the manual feed as a fallback oracle source", async () => {
+        const [manualPriceFeedPda] = await PublicKey.findProgramAddress(
+            [Buffer.from("manual_price")],
+            program.programId
+        );
+
+        // $10.00
+        await program.methods.setManualPrice(new anchor.BN(10000000), new anchor.BN(0), -6)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+                manualPriceFeed: manualPriceFeedPda,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        // The feed must parse as the declared kind
+        const bogus = Keypair.generate().publicKey;
+        try {
+            await program.methods.setOracleSource(1, { kind: 4, feed: bogus, feedId: new Array(32).fill(0) })
+                .accounts({
+                    admin: provider.wallet.publicKey,
+                    vaultState: vaultStatePda,
+                    newPriceFeed: bogus,
+                })
+                .rpc();
+            assert.fail("Unparseable feed should be rejected");
+        } catch (err) {
+            assert.include(err.toString(), "InvalidPriceFeed");
+        }
+
+        const source = { kind: 4, feed: manualPriceFeedPda, feedId: new Array(32).fill(0) };
+        await program.methods.setOracleSource(1, source)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+                newPriceFeed: manualPriceFeedPda,
+            })
+            .rpc();
+
+        const state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.equal(state.oracleSources[1].kind, 4);
+        assert.ok(state.oracleSources[1].feed.equals(manualPriceFeedPda));
+        assert.equal(state.minOracleSources, 1);
+
+        // The same account can't fill a second slot
+        try {
+            await program.methods.setOracleSource(2, source)
+                .accounts({
+                    admin: provider.wallet.publicKey,
+                    vaultState: vaultStatePda,
+                    newPriceFeed: manualPriceFeedPda,
+                })
+                .rpc();
+            assert.fail("Duplicate oracle source should fail");
+        } catch (err) {
+            assert.include(err.toString(), "InvalidOracleParams");
+        }
+
+        // Every configured source must be passed in, so leaving out the manual feed fails
+        const [userLoanPda] = await PublicKey.findProgramAddress(
+            [Buffer.from("user_loan"), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const userJusdiAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            provider.wallet.payer,
+            jusdiMint,
+            provider.wallet.publicKey
+        )).address;
+        try {
+            await program.methods.borrow(new anchor.BN(1000000))
+                .accounts({
+                    user: provider.wallet.publicKey,
+                    userLoan: userLoanPda,
+                    vaultState: vaultStatePda,
+                    userJusdiAccount: userJusdiAccount,
+                    userSkrAccount: userSkrAccount,
+                    jusdiMint: jusdiMint,
+                    skrPriceFeed: state.oracleSources[0].feed,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .rpc();
+            assert.fail("Borrow without every oracle source should fail");
+        } catch (err) {
+            assert.include(err.toString(), "OracleSourceMissing");
+        }
+
+        // Later tests price from the mock feed alone
+        await program.methods.setOracleSource(1, { kind: 0, feed: PublicKey.default, feedId: new Array(32).fill(0) })
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+                newPriceFeed: manualPriceFeedPda,
+            })
+            .rpc();
+    });
+
+    it("Records prices for the TWAP", async () => {
+        const [priceHistoryPda] = await PublicKey.findProgramAddress(
+            [Buffer.from("price_history")],
+            program.programId
+        );
+        await setMockPrice(10000000);
+        const state = await program.account.vaultState.fetch(vaultStatePda);
+
+        const recordPrice = () => program.methods.recordPrice()
+            .accounts({
+                keeper: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+                priceHistory: priceHistoryPda,
+                skrPriceFeed: state.oracleSources[0].feed,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        await recordPrice();
+
+        const history = await program.account.priceHistory.fetch(priceHistoryPda);
+        assert.equal(history.count, 1);
+        assert.equal(history.observations[0].price.toString(), "10000000000"); // $10 at 10^-9
+
+        // Observations must be spaced out over the window
+        try {
+            await recordPrice();
+            assert.fail("Second observation in the same interval should fail");
+        } catch (err) {
+            assert.include(err.toString(), "ObservationTooSoon");
+        }
+    });
+
+    it("Configures and resets the borrow circuit breaker", async () => {
+        // Halt borrowing on a 10% move within 10 minutes
+        await program.methods.setCircuitBreaker(1000, new anchor.BN(600))
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        let state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.equal(state.maxPriceDeviationBps, 1000);
+        assert.equal(state.priceDeviationWindowSecs.toString(), "600");
+        // Earlier borrows recorded the $10 mock price
+        assert.equal(state.lastPrice.toString(), "10000000000");
+
+        await program.methods.resetCircuitBreaker()
+            .accounts({
+                authority: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.equal(state.breakerTripped, false);
+        assert.equal(state.lastPriceAt.toString(), "0");
+    });
+
+    it("Moves the mock price through the real oracle checks", async () => {
+        const [userLoanPda] = await PublicKey.findProgramAddress(
+            [Buffer.from("user_loan"), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const userJusdiAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            provider.wallet.payer,
+            jusdiMint,
+            provider.wallet.publicKey
+        )).address;
+
+        const borrow = async (priceFeed) => program.methods.borrow(new anchor.BN(1000000))
+            .accounts({
+                user: provider.wallet.publicKey,
+                userLoan: userLoanPda,
+                vaultState: vaultStatePda,
+                userJusdiAccount: userJusdiAccount,
+                userSkrAccount: userSkrAccount,
+                jusdiMint: jusdiMint,
+                skrPriceFeed: priceFeed,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc();
+
+        // A price published two minutes ago is stale
+        const now = await provider.connection.getBlockTime(await provider.connection.getSlot());
+        let priceFeed = await setMockPrice(10000000, -6, now - 120);
+        try {
+            await borrow(priceFeed);
+            assert.fail("Stale price should be rejected");
+        } catch (err) {
+            assert.include(err.toString(), "StaleOracle");
+        }
+
+        // At $5 the 200 SKR collateral no longer supports the 600 jUSDi debt
+        priceFeed = await setMockPrice(5000000);
+        try {
+            await borrow(priceFeed);
+            assert.fail("Borrow after a price crash should fail");
+        } catch (err) {
+            assert.include(err.toString(), "BorrowExceedsLtv");
+        }
+
+        await setMockPrice(10000000);
+    });
+
+    it("Only accepts a stake pool account as the yield source", async () => {
+        const bogus = Keypair.generate().publicKey;
+        try {
+            await program.methods.setYieldSource(1)
+                .accounts({
+                    admin: provider.wallet.publicKey,
+                    vaultState: vaultStatePda,
+                    stakePool: bogus,
+                })
+                .rpc();
+            assert.fail("A non-pool account should be rejected");
+        } catch (err) {
+            assert.include(err.toString(), "InvalidStakePool");
+        }
+
+        await program.methods.setYieldSource(0)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+                stakePool: bogus,
+            })
+            .rpc();
+
+        const state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.equal(state.yieldSource, 0);
+        assert.ok(state.stakePool.equals(PublicKey.default));
+    });
+
+    it("Configures the simulated reward rate", async () => {
+        try {
+            await program.methods.setRewardRate(10001, new anchor.BN(0))
+                .accounts({
+                    admin: provider.wallet.publicKey,
+                    vaultState: vaultStatePda,
+                })
+                .rpc();
+            assert.fail("Rates above 100% APY should be rejected");
+        } catch (err) {
+            assert.include(err.toString(), "RewardRateTooHigh");
+        }
+
+        await program.methods.setRewardRate(1200, new anchor.BN(5000000))
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        let state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.equal(state.rewardRateBpsPerYear, 1200);
+        assert.equal(state.maxHarvestReward.toString(), "5000000");
+
+        await program.methods.setRewardRate(700, new anchor.BN(0))
+            .accounts({
+                admin: provider.wallet.publicKey,
+                vaultState: vaultStatePda,
+            })
+            .rpc();
+
+        state = await program.account.vaultState.fetch(vaultStatePda);
+        assert.equal(state.rewardRateBpsPerYear, 700);
+    });
 });